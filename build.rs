@@ -5,4 +5,3 @@ fn main() {
     
     napi_build::setup();
 }
-
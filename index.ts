@@ -237,16 +237,75 @@ const loadBinding = (): any => {
 
 const binding = loadBinding();
 
+/**
+ * Maps a machine-readable error reason to a display message
+ * Return undefined to keep the default (English) message
+ */
+export type ErrorTranslator = (reason: string, message: string) => string | undefined;
+
+let errorTranslator: ErrorTranslator | null = null;
+
+// Native errors carry their reason as a "[REASON] message" prefix
+const REASON_PATTERN = /^\[([A-Z0-9_]+)\] /;
+
+/**
+ * Move the native reason prefix into `code`/`reason` and apply the error translator
+ */
+const normalizeError = (error: any): any => {
+  const match = typeof error?.message === 'string' ? REASON_PATTERN.exec(error.message) : null;
+  if (!match) {
+    return error;
+  }
+
+  const reason = match[1];
+  const message = error.message.slice(match[0].length);
+  error.code = reason;
+  error.reason = reason;
+  error.message = (errorTranslator && errorTranslator(reason, message)) || message;
+  return error;
+};
+
+const callNative = <T>(fn: () => T): T => {
+  try {
+    return fn();
+  } catch (error) {
+    throw normalizeError(error);
+  }
+};
+
+const callNativeAsync = async <T>(fn: () => Promise<T>): Promise<T> => {
+  try {
+    return await fn();
+  } catch (error) {
+    throw normalizeError(error);
+  }
+};
+
+/**
+ * Set a translator for error messages (e.g. for localized UIs)
+ * `err.code` and `err.reason` always keep the machine-readable reason
+ * @param translator Translator callback, or null to restore the default messages
+ */
+export function setErrorTranslator(translator: ErrorTranslator | null): void {
+  errorTranslator = translator;
+}
+
 /**
  * APDU Transmit Result
  */
 export interface TransmitResult {
   /** Response data (excluding status word) */
   data: Buffer;
-  /** Status word byte 1 */
+  /** Status word byte 1 (of the last exchange, after any GET RESPONSE chain) */
   sw1: number;
   /** Status word byte 2 */
   sw2: number;
+  /** SW1 and SW2 combined (e.g. 0x9000) */
+  statusWord: number;
+  /** The status word is 90 00 */
+  isSuccess: boolean;
+  /** Number of GET RESPONSE commands issued (only set when chaining happened) */
+  getResponseIterations?: number;
 }
 
 /**
@@ -261,10 +320,253 @@ export interface CardStatus {
   mute: boolean;
   /** ATR (Answer To Reset) - identifies card type */
   atr?: Buffer;
+  /**
+   * Reader event counter, bumped on every insertion and removal (reader status only).
+   * A different count with `present` still true means the card was swapped between polls.
+   */
+  changeCount?: number;
+  /** Some application, possibly this one, is connected to the card (reader status only) */
+  inUse?: boolean;
+  /** An application holds the card in exclusive mode, so connects fail (reader status only) */
+  exclusive?: boolean;
+  /** Card is present but not powered (reader status only) */
+  unpowered?: boolean;
+}
+
+/**
+ * Everything SCardStatus reports for a connected card
+ */
+export interface FullCardStatus {
+  /** Names of the reader the connection is on (more than one for a reader with aliases) */
+  readerNames: string[];
+  /** No card in the reader */
+  absent: boolean;
+  /** A card is in the reader */
+  present: boolean;
+  /** The card is in position for use */
+  swallowed: boolean;
+  /** The card is powered */
+  powered: boolean;
+  /** Card was reset and awaits protocol negotiation */
+  negotiable: boolean;
+  /** A protocol has been set */
+  specific: boolean;
+  /** Active protocol (undefined for a direct connection) */
+  protocol?: Protocol;
+  /** ATR of the card */
+  atr?: Buffer;
+}
+
+/**
+ * Reader name with card presence
+ */
+export interface ReaderDetail {
+  /** Reader name */
+  name: string;
+  /** Card is present in reader */
+  present: boolean;
+  /** ATR of the present card */
+  atr?: Buffer;
+}
+
+/**
+ * Reader name with its full card status
+ */
+export interface ReaderWithStatus {
+  /** Reader name */
+  name: string;
+  /** Card status, as getStatus() reports it */
+  status: CardStatus;
+}
+
+/**
+ * Protocol parameters negotiated at connect
+ */
+export interface ProtocolParams {
+  /** Active protocol (T0, T1 or Raw) */
+  protocol?: Protocol;
+  /** Clock rate conversion index (FI) from the ATR's TA1 */
+  fi: number;
+  /** Baud rate adjustment index (DI) from the ATR's TA1 */
+  di: number;
+  /** Current F value reported by the reader, if supported */
+  currentF?: number;
+  /** Current D value reported by the reader, if supported */
+  currentD?: number;
+}
+
+/**
+ * Location of a signed data block and its signature on a Thai ID card (byte offsets in the MOI applet)
+ */
+export interface SignedBlockLayout {
+  dataOffset: number;
+  dataLength: number;
+  signatureOffset: number;
+  signatureLength: number;
+  certOffset?: number;
+  certLength?: number;
+}
+
+/**
+ * Raw signed data read from the card, for offline verification
+ */
+export interface SignedBlock {
+  /** Signed data */
+  data: Buffer;
+  /** Signature over the data */
+  signature: Buffer;
+  /** Signer certificate, if requested */
+  cert?: Buffer;
+}
+
+/**
+ * Personal data read from a Thai national ID card
+ */
+export interface ThaiIdData {
+  /** 13-digit citizen ID */
+  cid: string;
+  /** Thai name, '#'-separated (title#first#middle#last) */
+  thaiName: string;
+  /** English name, '#'-separated (title#first#middle#last) */
+  englishName: string;
+  /** Date of birth, YYYYMMDD in the Buddhist era */
+  birthDate: string;
+  /** Issue date, YYYYMMDD in the Buddhist era (undefined on cards without it) */
+  issueDate?: string;
+  /** Expiry date, YYYYMMDD in the Buddhist era (undefined on cards without it); see thaiDateToIso */
+  expireDate?: string;
+}
+
+/**
+ * Everything readThaiIdAllAsync() reads from a Thai national ID card
+ */
+export interface ThaiIdCard {
+  /** Citizen ID, names and dates */
+  data: ThaiIdData;
+  /** Registered address */
+  address: ThaiIdAddress;
+  /** JPEG photo */
+  photo: Buffer;
+}
+
+/**
+ * Registered address on a Thai national ID card
+ * Parts keep the card's own prefixes (e.g. หมู่ที่, ตำบล); absent parts are empty strings
+ */
+export interface ThaiIdAddress {
+  houseNo: string;
+  /** Village number (หมู่ที่) */
+  moo: string;
+  /** Lane (ตรอก) */
+  trok: string;
+  /** Alley (ซอย) */
+  soi: string;
+  road: string;
+  /** Sub-district (ตำบล/แขวง) */
+  tambon: string;
+  /** District (อำเภอ/เขต) */
+  amphoe: string;
+  /** Province (จังหวัด) */
+  changwat: string;
+}
+
+/**
+ * A reader whose state changed
+ */
+export interface ReaderChange {
+  /** Reader name (PNP_NOTIFICATION_READER for reader attach/detach) */
+  readerName: string;
+  /** New card status */
+  status: CardStatus;
+}
+
+/**
+ * Build and platform details from getEnvironmentInfo()
+ */
+export interface EnvironmentInfo {
+  /** Version of this library */
+  crateVersion: string;
+  /** Minimum pcsc crate version it requires (the build may use a newer 2.x) */
+  pcscVersion: string;
+  /** PC/SC implementation: pcsc-lite, WinSCard or PCSC.framework */
+  pcscBackend: string;
+  /** `<os>-<arch>`, e.g. linux-x86_64 */
+  platform: string;
+}
+
+/**
+ * A PC/SC part 10 reader feature
+ */
+export interface ReaderFeature {
+  /** Feature tag (see FeatureTag) */
+  tag: number;
+  /** Control code to pass to control() */
+  controlCode: number;
+}
+
+/**
+ * PC/SC part 10 feature tags reported by Card.getFeatures()
+ */
+export enum FeatureTag {
+  VerifyPinStart = 0x01,
+  VerifyPinFinish = 0x02,
+  ModifyPinStart = 0x03,
+  ModifyPinFinish = 0x04,
+  GetKeyPressed = 0x05,
+  VerifyPinDirect = 0x06,
+  ModifyPinDirect = 0x07,
+  IfdPinProperties = 0x0A,
+  GetTlvProperties = 0x12,
+}
+
+/** Pseudo-reader name reported when readers are attached or detached */
+export const PNP_NOTIFICATION_READER = '\\\\?PnP?\\Notification';
+
+/**
+ * ATR broken into its parts
+ */
+export interface AtrInfo {
+  /** 'direct' (TS = 3B) or 'inverse' (TS = 3F) */
+  convention: string;
+  /** Format byte */
+  t0: number;
+  /** Protocols indicated by the ATR (0 = T=0, 1 = T=1, ...) */
+  protocols: number[];
+  /** Historical bytes */
+  historicalBytes: Buffer;
+  /** Thai ID chip generation, if known */
+  thaiIdGeneration?: number;
+}
+
+/**
+ * Contents of an elementary file
+ */
+export interface FileContents {
+  /** File data */
+  data: Buffer;
+  /** File size declared in the FCP, if the card reported one */
+  declaredSize?: number;
+  /** False when fewer bytes were read than the FCP declared */
+  complete: boolean;
+}
+
+/**
+ * Outcome of a PIN verification
+ */
+export interface VerifyResult {
+  /** PIN accepted (90 00) */
+  verified: boolean;
+  /** PIN is blocked (69 83, or 63 C0 after the last failed try) */
+  blocked: boolean;
+  /** Tries left from 63 Cx (0 when blocked); not every card reports it */
+  triesRemaining?: number;
+  /** SW1 and SW2 combined */
+  statusWord: number;
 }
 
 /**
  * Share Mode for card connection
+ * Mirrors the native enum; connect/reconnect reject values outside it
  */
 export enum ShareMode {
   /** Shared mode - multiple applications can access the card */
@@ -277,6 +579,7 @@ export enum ShareMode {
 
 /**
  * Preferred Protocol for card connection
+ * Mirrors the native enum; connect/reconnect reject values outside it
  */
 export enum Protocol {
   /** T=0 protocol */
@@ -289,6 +592,18 @@ export enum Protocol {
   Any = 3,
 }
 
+/**
+ * Scope of the PC/SC context
+ */
+export enum Scope {
+  /** Current user (default) */
+  User = 0,
+  /** System-wide, e.g. for services and daemons */
+  System = 1,
+  /** Current terminal */
+  Terminal = 2,
+}
+
 /**
  * Disposition when disconnecting card
  */
@@ -303,6 +618,36 @@ export enum Disposition {
   EjectCard = 3,
 }
 
+/**
+ * Common reader/card attributes (SCARD_ATTR_*) for Card.getAttribute()
+ */
+export enum Attribute {
+  /** Reader vendor name */
+  VendorName = 0x00010100,
+  /** Reader vendor-defined type */
+  VendorIfdType = 0x00010101,
+  /** Reader vendor-defined version */
+  VendorIfdVersion = 0x00010102,
+  /** Reader serial number */
+  VendorIfdSerialNo = 0x00010103,
+  /** Maximum IFSD supported by the reader */
+  MaxIfsd = 0x00030125,
+  /** Card presence (0 = absent, 1 = present, 2 = swallowed) */
+  IccPresence = 0x00090300,
+  /** Card ATR */
+  AtrString = 0x00090303,
+}
+
+/**
+ * MIFARE Classic key type for Card.mifareAuthenticate()
+ */
+export enum MifareKeyType {
+  /** Key A */
+  A = 0x60,
+  /** Key B */
+  B = 0x61,
+}
+
 /**
  * Smart Card Reader
  * 
@@ -324,25 +669,121 @@ export enum Disposition {
 export class SmartCardReader {
   private native: any;
 
-  constructor() {
-    this.native = new binding.SmartCardReader();
+  /**
+   * @param warmUp Prime the PC/SC context with a throwaway call, smoothing out the first real call (default: false)
+   * @param scope Context scope (default: User; services may need System)
+   */
+  constructor(warmUp: boolean = false, scope: Scope = Scope.User) {
+    this.native = callNative(() => new binding.SmartCardReader(warmUp, scope));
+  }
+
+  /**
+   * Check whether the PC/SC context is still usable
+   * It stops being valid when the smart card service restarts; the next operation re-establishes it
+   * @returns True if the context is valid
+   */
+  isValid(): boolean {
+    return callNative(() => this.native.isValid());
   }
 
   /**
-   * List all available card readers
+   * Replace the PC/SC context with a new one, e.g. after the smart card service restarted
+   * Not usually needed: operations re-establish an invalid context by themselves
+   */
+  reestablish(): void {
+    callNative(() => this.native.reestablish());
+  }
+
+  /**
+   * List all available card readers, or only those in a reader group
+   * @param group Reader group name (e.g. "SCard$DefaultReaders"); all readers when omitted
    * @returns Array of reader names
    */
-  listReaders(): string[] {
-    return this.native.listReaders();
+  listReaders(group?: string): string[] {
+    return callNative(() => this.native.listReaders(group ?? undefined));
+  }
+
+  /**
+   * List the reader groups defined on this system
+   * @returns Array of group names
+   */
+  listReaderGroups(): string[] {
+    return callNative(() => this.native.listReaderGroups());
+  }
+
+  /**
+   * Re-list the readers and refresh the cached list used by connect, getStatus and waitForCard
+   *
+   * Lookups already refresh the cache when a reader name isn't found, so this is only
+   * needed to pick up removed readers early.
+   * @returns Array of reader names
+   */
+  refreshReaders(): string[] {
+    return callNative(() => this.native.refreshReaders());
+  }
+
+  /**
+   * List all readers together with card presence and ATR in one call
+   * @returns Reader details
+   */
+  listReadersDetailed(): ReaderDetail[] {
+    return callNative(() => this.native.listReadersDetailed());
+  }
+
+  /**
+   * List all readers with their full card status (present/empty/mute/ATR and flags) in one call
+   * Cheaper and race-free compared with listReaders() followed by getStatus() per reader
+   * @returns Reader names with status
+   */
+  listReadersWithStatus(): ReaderWithStatus[] {
+    return callNative(() => this.native.listReadersWithStatus());
   }
 
   /**
    * Get card status for a specific reader
    * @param readerName Reader name
+   * @param timeoutMs Status query timeout in milliseconds (default: setStatusTimeoutMs value, initially 0)
+   * @returns Card status information, including the ATR when a responsive card is present (no connect needed)
+   */
+  getStatus(readerName: string, timeoutMs?: number): CardStatus {
+    return callNative(() => this.native.getStatus(readerName, timeoutMs));
+  }
+
+  /**
+   * Get card status for the reader at a position in the reader list
+   * @param index Position in listReaders() order (0 = first reader)
+   * @param timeoutMs Status query timeout in milliseconds (default: setStatusTimeoutMs value, initially 0)
    * @returns Card status information
+   * @throws code READER_NOT_FOUND when there is no reader at that index
+   */
+  getStatusByIndex(index: number, timeoutMs?: number): CardStatus {
+    return callNative(() => this.native.getStatusByIndex(index, timeoutMs));
+  }
+
+  /**
+   * Check whether a card is in the reader, cheaply enough for polling several times a second
+   * Repeated calls for the same reader reuse its cached state instead of re-listing readers
+   * @param readerName Reader name
+   * @returns True if a card is present
+   */
+  isCardPresent(readerName: string): boolean {
+    return callNative(() => this.native.isCardPresent(readerName));
+  }
+
+  /**
+   * Set the default getStatus timeout, for drivers that hang on a zero timeout
+   * @param timeoutMs Timeout in milliseconds
+   */
+  setStatusTimeoutMs(timeoutMs: number): void {
+    callNative(() => this.native.setStatusTimeoutMs(timeoutMs));
+  }
+
+  /**
+   * Get the default getStatus timeout
+   * @returns Timeout in milliseconds
    */
-  getStatus(readerName: string): CardStatus {
-    return this.native.getStatus(readerName);
+  getStatusTimeoutMs(): number {
+    return callNative(() => this.native.getStatusTimeoutMs());
   }
 
   /**
@@ -350,25 +791,158 @@ export class SmartCardReader {
    * @param readerName Reader name
    * @param shareMode Share mode (default: Shared)
    * @param preferredProtocol Preferred protocol (default: Any - auto-detect)
+   * @param maxRetries Retries when another application holds the card (SHARING_VIOLATION) (default: 0)
+   * @param retryDelayMs Delay between those retries in milliseconds (default: 100)
    * @returns Connected card instance
+   * @throws code NO_CARD_PRESENT when the slot is empty, CARD_REMOVED if the card was pulled mid-connect
    */
   connect(
     readerName: string,
     shareMode: ShareMode = ShareMode.Shared,
+    preferredProtocol?: Protocol,
+    maxRetries?: number,
+    retryDelayMs?: number
+  ): Card {
+    // Left undefined, the native side picks Any (or none for Direct connections)
+    return new Card(callNative(() => this.native.connect(readerName, shareMode, preferredProtocol, maxRetries, retryDelayMs)));
+  }
+
+  /**
+   * Connect with a preferred share mode, settling for a fallback when another application holds the card
+   * Typically Exclusive with a Shared fallback; check card.getShareMode() for the mode obtained
+   * @param readerName Reader name
+   * @param preferredShareMode Share mode to try first (default: Exclusive)
+   * @param fallbackShareMode Share mode to use on SHARING_VIOLATION (default: Shared)
+   * @param preferredProtocol Preferred protocol (default: Any - auto-detect)
+   * @returns Connected card instance
+   */
+  connectPreferred(
+    readerName: string,
+    preferredShareMode: ShareMode = ShareMode.Exclusive,
+    fallbackShareMode: ShareMode = ShareMode.Shared,
     preferredProtocol?: Protocol
   ): Card {
-    const protocol = preferredProtocol !== undefined ? preferredProtocol : 3; // Any
-    return new Card(this.native.connect(readerName, shareMode, protocol));
+    return new Card(callNative(() => this.native.connectPreferred(readerName, preferredShareMode, fallbackShareMode, preferredProtocol)));
+  }
+
+  /**
+   * Connect to the card in the reader at a position in the reader list
+   *
+   * Handy for single-reader setups, where reader names differ between platforms.
+   * @param index Position in listReaders() order (0 = first reader)
+   * @param shareMode Share mode (default: Shared)
+   * @param preferredProtocol Preferred protocol (default: Any - auto-detect)
+   * @returns Connected card instance
+   * @throws code READER_NOT_FOUND when there is no reader at that index
+   */
+  connectByIndex(index: number, shareMode: ShareMode = ShareMode.Shared, preferredProtocol?: Protocol): Card {
+    return new Card(callNative(() => this.native.connectByIndex(index, shareMode, preferredProtocol)));
   }
 
   /**
-   * Wait for card status change
+   * Wait for a card to be inserted, then connect to it
+   *
+   * If no card arrives within the timeout, this fails like connect with code NO_CARD_PRESENT.
+   * @param readerName Reader name
+   * @param shareMode Share mode (default: Shared)
+   * @param preferredProtocol Preferred protocol (default: Any - auto-detect)
+   * @param timeoutMs Time to wait for a card in milliseconds (default: 30000)
+   * @returns Connected card instance
+   */
+  async connectWhenPresent(
+    readerName: string,
+    shareMode: ShareMode = ShareMode.Shared,
+    preferredProtocol?: Protocol,
+    timeoutMs: number = 30000
+  ): Promise<Card> {
+    await this.waitForCard(readerName, timeoutMs);
+    return this.connect(readerName, shareMode, preferredProtocol);
+  }
+
+  /**
+   * Space out operations after a failure so retries can't hammer a broken reader
+   * Applies to this reader and every card connected through it
+   * @param intervalMs Minimum delay after a failed operation in milliseconds (default: 0)
+   */
+  setMinOpIntervalMs(intervalMs: number): void {
+    callNative(() => this.native.setMinOpIntervalMs(intervalMs));
+  }
+
+  /**
+   * Enable dry-run mode: connect returns cards that log APDUs (via setLogger) instead of sending them
+   * @param enabled Dry-run enabled
+   */
+  setDryRun(enabled: boolean): void {
+    callNative(() => this.native.setDryRun(enabled));
+  }
+
+  /**
+   * Wait until a card is inserted (returns at once if one is already present)
    * @param readerName Reader name
    * @param timeoutMs Timeout in milliseconds (default: 30000)
-   * @returns Card status when change detected
+   * @returns Card status; present is false if no card arrived within the timeout
    */
   async waitForCard(readerName: string, timeoutMs: number = 30000): Promise<CardStatus> {
-    return await this.native.waitForCard(readerName, timeoutMs);
+    return callNativeAsync(() => this.native.waitForCard(readerName, timeoutMs));
+  }
+
+  /**
+   * Wait until the card is removed (returns at once if the slot is already empty)
+   * @param readerName Reader name
+   * @param timeoutMs Timeout in milliseconds (default: 30000)
+   * @returns Card status; present is still true if the card wasn't removed within the timeout
+   */
+  async waitForCardRemoval(readerName: string, timeoutMs: number = 30000): Promise<CardStatus> {
+    return callNativeAsync(() => this.native.waitForCardRemoval(readerName, timeoutMs));
+  }
+
+  /**
+   * Wait until a reader is attached or detached
+   * @param timeoutMs Timeout in milliseconds (default: 30000)
+   * @returns The new reader list, or undefined if nothing changed within the timeout
+   */
+  async waitForReaderChange(timeoutMs: number = 30000): Promise<string[] | undefined> {
+    const readers = await callNativeAsync(() => this.native.waitForReaderChange(timeoutMs));
+    return readers ?? undefined;
+  }
+
+  /**
+   * Abort pending waits (waitForCard, waitForCardRemoval, waitForAnyCard, ...)
+   * They reject with code CANCELLED; the monitor is not affected
+   */
+  cancel(): void {
+    callNative(() => this.native.cancel());
+  }
+
+  /**
+   * Watch a reader in the background and get a callback on every card insertion or removal
+   * The callback also receives the current status right away. Replaces any running monitor;
+   * call stopMonitor() when done, or the process stays alive.
+   *
+   * @param readerName Reader name
+   * @param callback Called with the new card status
+   */
+  startMonitor(readerName: string, callback: (status: CardStatus) => void): void {
+    callNative(() => this.native.startMonitor(readerName, callback));
+  }
+
+  /**
+   * Stop the monitor started by startMonitor()
+   */
+  stopMonitor(): void {
+    callNative(() => this.native.stopMonitor());
+  }
+
+  /**
+   * Wait for a card event on any of several readers with a single PC/SC call
+   * @param readerNames Readers to watch
+   * @param timeoutMs Timeout in milliseconds (default: 30000)
+   * @param includePnp Also report readers being attached/detached (as PNP_NOTIFICATION_READER)
+   * @returns The reader that changed and its status, or undefined on timeout
+   */
+  async waitForAnyCard(readerNames: string[], timeoutMs: number = 30000, includePnp: boolean = false): Promise<ReaderChange | undefined> {
+    const change = await callNativeAsync(() => this.native.waitForAnyCard(readerNames, timeoutMs, includePnp));
+    return change ?? undefined;
   }
 }
 
@@ -385,11 +959,56 @@ export class Card {
   }
 
   /**
-   * Get ATR (Answer To Reset) - identifies card type
+   * Create a card that isn't attached to any reader
+   * Every transmit is logged (via setLogger) and answered with a synthetic 90 00
+   * @returns Dry-run card instance
+   */
+  static dryRun(): Card {
+    return new Card(callNative(() => binding.Card.dryRun()));
+  }
+
+  /**
+   * Log commands (via setLogger) instead of sending them, answering each with a synthetic 90 00
+   * @param enabled Dry-run enabled
+   */
+  setDryRun(enabled: boolean): void {
+    callNative(() => this.native.setDryRun(enabled));
+  }
+
+  /**
+   * Check each command's Lc against its length before sending, throwing with code INVALID_APDU
+   * on a mismatch (on by default). Extended-length commands and proprietary classes (CLA 80-FF)
+   * are not checked
+   * @param enabled Validation enabled
+   */
+  setValidateApdus(enabled: boolean): void {
+    callNative(() => this.native.setValidateApdus(enabled));
+  }
+
+  /**
+   * Set the CLA byte of the GET RESPONSE sent when a command answers 61xx
+   * Proprietary applets that ignore 00 C0 otherwise leave responses truncated
+   * @param cla Class byte (default: 0x00)
+   */
+  setGetResponseCla(cla: number): void {
+    callNative(() => this.native.setGetResponseCla(cla));
+  }
+
+  /**
+   * Get ATR (Answer To Reset) - identifies card type, captured at connect time
+   * Refreshed by reconnect(), reset() and powerOff(); undefined once the card is disconnected
    * @returns ATR buffer or undefined if not available
    */
   getATR(): Buffer | undefined {
-    return this.native.getAtr();
+    return callNative(() => this.native.getAtr()) ?? undefined;
+  }
+
+  /**
+   * Read the ATR from the card again and keep it for getATR()
+   * @returns ATR buffer or undefined if the card doesn't report one
+   */
+  refreshATR(): Buffer | undefined {
+    return callNative(() => this.native.refreshAtr() ?? undefined);
   }
 
   /**
@@ -397,24 +1016,429 @@ export class Card {
    * @returns Card status information
    */
   getStatus(): CardStatus {
-    return this.native.getStatus();
+    return callNative(() => this.native.getStatus());
+  }
+
+  /**
+   * Get everything PC/SC reports for this connection, for diagnostics
+   * @returns Reader names (which reader the connection landed on), card state flags, protocol and ATR
+   */
+  getFullStatus(): FullCardStatus {
+    return callNative(() => this.native.getFullStatus());
+  }
+
+  /**
+   * Get the share mode obtained at connect (or the last reconnect/reset)
+   * @returns Share mode, e.g. the fallback after connectPreferred hit SHARING_VIOLATION
+   */
+  getShareMode(): ShareMode {
+    return callNative(() => this.native.getShareMode());
+  }
+
+  /**
+   * Get the protocol negotiated at connect, e.g. to decide how to send case-4 APDUs under T=0
+   * @returns Active protocol, or undefined for a direct connection
+   */
+  getActiveProtocol(): Protocol | undefined {
+    return callNative(() => this.native.getActiveProtocol() ?? undefined);
+  }
+
+  /**
+   * Get the negotiated protocol parameters (for diagnosing slow cards)
+   * @returns Protocol and FI/DI indices (1/1 when the ATR has no TA1)
+   */
+  protocolParameters(): ProtocolParams {
+    return callNative(() => this.native.protocolParameters());
+  }
+
+  /**
+   * Get the largest information field the reader accepts (SCARD_ATTR_MAX_IFSD)
+   * Useful as the readBinary chunk size or for splitting chained commands
+   * @returns Maximum length in bytes (255 when the reader doesn't report it)
+   */
+  getMaxApduLength(): number {
+    return callNative(() => this.native.getMaxApduLength());
   }
 
   /**
    * Transmit APDU command to card
-   * Automatically handles GET RESPONSE for extended data
+   * Automatically handles GET RESPONSE for extended data, and re-sends once with the
+   * card's Le on 6Cxx (wrong length). Under T=0, case-4 commands are sent without their Le
+   * and the response is fetched with GET RESPONSE, so the same APDU works on T=0 and T=1
    * 
    * @param command APDU command buffer
-   * @param responseLength Expected response length (default: 40)
+   * @param responseLength Expected response length (default: 40); a hint, the buffer grows if the card sends more.
+   *   A larger Le in the command wins: Le 00 counts as 256 bytes (short APDU) and 00 00 as 65536 (extended)
    * @param maxGetResponse Maximum GET RESPONSE iterations (default: 3)
+   * @param timeoutMs Fail with code TIMEOUT if the card hasn't answered in time (default: no timeout).
+   *   Best effort: PC/SC can't abort a blocked transmit, so the card stays locked until the reader returns
    * @returns Transmit result with data and status word
    */
   transmit(
     command: Buffer,
     responseLength: number = 40,
-    maxGetResponse?: number
+    maxGetResponse?: number,
+    timeoutMs?: number
   ): TransmitResult {
-    return this.native.transmit(command, responseLength, maxGetResponse);
+    return callNative(() => this.native.transmit(command, responseLength, maxGetResponse, timeoutMs));
+  }
+
+  /**
+   * Send several APDUs in one call, holding the card lock throughout
+   * Each command gets the same GET RESPONSE / 6Cxx handling as transmit()
+   *
+   * @param commands APDU commands, sent in order
+   * @param responseLength Expected response length for each command (default: 40)
+   * @param options useTransaction wraps the batch in a PC/SC transaction; stopOnError stops after
+   *   the first result that isn't 90 00 (that result is included)
+   * @returns One result per command sent
+   */
+  transmitBatch(
+    commands: Buffer[],
+    responseLength: number = 40,
+    options: { useTransaction?: boolean; stopOnError?: boolean } = {}
+  ): TransmitResult[] {
+    return callNative(() => this.native.transmitBatch(commands, responseLength, options.useTransaction, options.stopOnError));
+  }
+
+  /**
+   * Send APDUs written as hex strings, in order, collecting each response
+   * Spaces are allowed (e.g. "00 A4 04 00 07 A0 00 00 00 03 10 10"); every string is checked
+   * before anything is sent, and a bad one throws with code INVALID_ARGUMENT naming its index
+   *
+   * @param apdus APDU commands as hex
+   * @param stopOnError Stop after the first result that isn't 90 00 (default: false)
+   * @returns One result per command sent
+   */
+  runScript(apdus: string[], stopOnError?: boolean): TransmitResult[] {
+    return callNative(() => this.native.runScript(apdus, stopOnError));
+  }
+
+  /**
+   * Transmit APDU command without blocking the event loop
+   * Same behaviour as transmit(), but the PC/SC call runs on a worker thread.
+   * Calls on the same card are serialized.
+   *
+   * @param command APDU command buffer
+   * @param responseLength Expected response length (default: 40)
+   * @param maxGetResponse Maximum GET RESPONSE iterations (default: 3)
+   * @param timeoutMs Reject with code TIMEOUT if the card hasn't answered in time (default: no timeout); best effort as for transmit()
+   * @returns Promise of the transmit result
+   */
+  async transmitAsync(command: Buffer, responseLength: number = 40, maxGetResponse?: number, timeoutMs?: number): Promise<TransmitResult> {
+    return callNativeAsync(() => this.native.transmitAsync(command, responseLength, maxGetResponse, timeoutMs));
+  }
+
+  /**
+   * Send a reader control command (SCardControl), e.g. a pinpad feature request
+   * Works on a ShareMode.Direct connection with no card present
+   *
+   * @param controlCode Platform IOCTL value (use ctlCode() to build it)
+   * @param data Input buffer
+   * @returns Raw response bytes
+   */
+  control(controlCode: number, data: Buffer = Buffer.alloc(0)): Buffer {
+    return callNative(() => this.native.control(controlCode, data));
+  }
+
+  /**
+   * List the reader's PC/SC part 10 features (CM_IOCTL_GET_FEATURE_REQUEST), e.g. to find a pinpad
+   * @returns Feature tags with their control codes; empty for readers without part 10 support
+   */
+  getFeatures(): ReaderFeature[] {
+    return callNative(() => this.native.getFeatures());
+  }
+
+  /**
+   * Verify a PIN typed on the reader's keypad, so it never passes through the application
+   * (FEATURE_VERIFY_PIN_DIRECT)
+   *
+   * @param controlCode Control code of FeatureTag.VerifyPinDirect from getFeatures()
+   * @param pinVerifyStructure PC/SC part 10 PIN_VERIFY_STRUCTURE, including the VERIFY APDU
+   * @returns Outcome as for verifyPin()
+   * @throws code TIMEOUT if nothing was entered in time, CANCELLED if the user cancelled on the reader
+   */
+  verifyPinDirect(controlCode: number, pinVerifyStructure: Buffer): VerifyResult {
+    return callNative(() => this.native.verifyPinDirect(controlCode, pinVerifyStructure));
+  }
+
+  /**
+   * Read a reader/card attribute (SCardGetAttrib) without sending an APDU
+   * Throws with code ATTRIBUTE_UNSUPPORTED if the reader doesn't provide it
+   *
+   * @param attrId Attribute (see the Attribute enum for common values)
+   * @returns Raw attribute bytes
+   */
+  getAttribute(attrId: Attribute | number): Buffer {
+    return callNative(() => this.native.getAttribute(attrId));
+  }
+
+  /**
+   * Largest command data field sent in a single APDU
+   * @returns Maximum data length in bytes
+   */
+  maxCommandData(): number {
+    return callNative(() => this.native.maxCommandData());
+  }
+
+  /**
+   * Send a command whose data may exceed one APDU, using ISO 7816 command chaining
+   * The data is split by maxCommandData(); every block but the last has the CLA chaining bit (0x10) set.
+   * Data that fits in one APDU is sent unchained, so this is safe for any command size under T=0 or T=1.
+   *
+   * @param cla Class byte
+   * @param ins Instruction byte
+   * @param p1 Parameter 1
+   * @param p2 Parameter 2
+   * @param data Command data of any length
+   * @param responseLength Expected response length of the final block (default: 256)
+   * @returns Result of the final block, or of the first block that didn't return 90 00
+   */
+  transmitLarge(cla: number, ins: number, p1: number, p2: number, data: Buffer, responseLength?: number): TransmitResult {
+    return callNative(() => this.native.transmitLarge(cla, ins, p1, p2, data, responseLength));
+  }
+
+  /**
+   * Read a record without knowing its length
+   * Issues READ RECORD with Le=00 and re-issues with the card's length on 6Cxx
+   *
+   * @param recordNumber Record number
+   * @param sfi Short EF identifier (default: current EF)
+   * @returns Record data
+   */
+  readRecord(recordNumber: number, sfi?: number): Buffer {
+    return callNative(() => this.native.readRecord(recordNumber, sfi));
+  }
+
+  /**
+   * SELECT an application by AID, following up 61xx/6Cxx like transmit
+   *
+   * @param aid Application identifier (5 to 16 bytes)
+   * @param cla Class byte (default: 0x00; some proprietary applets want 0x80)
+   * @returns FCI data and status; the applet was selected when statusWord is 0x9000
+   */
+  selectApplet(aid: Buffer, cla?: number): TransmitResult {
+    return callNative(() => this.native.selectApplet(aid, cla));
+  }
+
+  /**
+   * Read the NDEF message from an NFC Forum Type 4 tag (e.g. an NFC business card)
+   * Selects the NDEF application and Capability Container, then reads the NDEF file
+   *
+   * @returns NDEF message bytes without the 2-byte length prefix (empty for an empty tag)
+   * @throws code NDEF_NOT_FOUND when the tag has no NDEF application
+   */
+  readNdef(): Buffer {
+    return callNative(() => this.native.readNdef());
+  }
+
+  /**
+   * Read a contactless card's UID with the reader pseudo-APDU FF CA 00 00 00
+   * Handled by the reader itself, so no applet needs to be selected
+   *
+   * @returns UID bytes (status word stripped)
+   * @throws code FUNCTION_UNSUPPORTED when the reader doesn't implement the command
+   */
+  getUid(): Buffer {
+    return callNative(() => this.native.getUid());
+  }
+
+  /**
+   * Load a MIFARE key into the reader's volatile key slot (FF 82)
+   * @param key 6-byte key
+   * @param keySlot Reader key slot (default: 0)
+   */
+  mifareLoadKey(key: Buffer, keySlot: number = 0): void {
+    callNative(() => this.native.mifareLoadKey(key, keySlot));
+  }
+
+  /**
+   * Authenticate a MIFARE Classic block with a loaded key (FF 86)
+   * @param block Block number
+   * @param keyType Key A or B
+   * @param keySlot Reader key slot the key was loaded into (default: 0)
+   * @throws code AUTHENTICATION_FAILED when the card rejects the key
+   */
+  mifareAuthenticate(block: number, keyType: MifareKeyType, keySlot: number = 0): void {
+    callNative(() => this.native.mifareAuthenticate(block, keyType, keySlot));
+  }
+
+  /**
+   * Read a 16-byte MIFARE Classic block (FF B0); authenticate its sector first
+   * @param block Block number
+   * @returns Block data
+   */
+  mifareReadBlock(block: number): Buffer {
+    return callNative(() => this.native.mifareReadBlock(block));
+  }
+
+  /**
+   * Write a 16-byte MIFARE Classic block (FF D6); authenticate its sector first
+   * @param block Block number
+   * @param data 16 bytes of block data
+   */
+  mifareWriteBlock(block: number, data: Buffer): void {
+    callNative(() => this.native.mifareWriteBlock(block, data));
+  }
+
+  /**
+   * Verify a PIN with VERIFY (00 20 00 p2)
+   * A wrong or blocked PIN is reported in the result rather than thrown; the PIN is never logged
+   *
+   * @param p2 PIN reference (card specific, e.g. 0x80 or 0x81)
+   * @param pin PIN bytes as the card expects them; empty to only read the retry counter
+   * @returns Whether the PIN was accepted, with the remaining tries when reported
+   */
+  verifyPin(p2: number, pin: Buffer): VerifyResult {
+    return callNative(() => this.native.verifyPin(p2, pin));
+  }
+
+  /**
+   * SELECT an application, verify its PIN and read from the current EF, all in one transaction
+   *
+   * @param aid Application identifier (5 to 16 bytes)
+   * @param pin PIN bytes as the card expects them
+   * @param offset Offset to start reading at
+   * @param length Number of bytes to read
+   * @param pinReference VERIFY P2 (default: 0x80)
+   * @returns Data read
+   * @throws code FILE_NOT_FOUND if the application or EF is missing, SELECT_FAILED for other SELECT errors,
+   *   WRONG_PIN (the message has the tries left when the card reports them) or PIN_BLOCKED
+   */
+  readProtected(aid: Buffer, pin: Buffer, offset: number, length: number, pinReference?: number): Buffer {
+    return callNative(() => this.native.readProtected(aid, pin, offset, length, pinReference));
+  }
+
+  /**
+   * Read part of the currently selected EF with repeated READ BINARY commands
+   * Offsets above 0x7FFF use READ BINARY B1 with an offset data object
+   *
+   * @param offset Start offset
+   * @param length Number of bytes to read
+   * @param chunkSize Bytes per READ BINARY (1-256, default: 255)
+   * @returns The data read; shorter than length if the file ended first
+   */
+  readBinary(offset: number, length: number, chunkSize?: number): Buffer {
+    return callNative(() => this.native.readBinary(offset, length, chunkSize));
+  }
+
+  /**
+   * Read an elementary file by file ID
+   * SELECTs the file requesting its FCP, then READ BINARYs up to the declared size
+   *
+   * @param fileId File identifier (e.g. Buffer.from([0x01, 0x1E]))
+   * @returns File data with the FCP-declared size
+   */
+  readFile(fileId: Buffer): FileContents {
+    return callNative(() => this.native.readFile(fileId));
+  }
+
+  /**
+   * Read EF.COM from the ICAO LDS applet
+   * Plain SELECT/READ BINARY only; BAC/PACE is left to the caller
+   *
+   * @returns Raw EF.COM bytes
+   */
+  readEfCom(): Buffer {
+    return callNative(() => this.native.readEfCom());
+  }
+
+  /**
+   * Read a data group from the ICAO LDS applet
+   * Plain SELECT/READ BINARY only; BAC/PACE is left to the caller
+   *
+   * @param dgNumber Data group number (1-16)
+   * @returns Raw data group bytes
+   */
+  readDg(dgNumber: number): Buffer {
+    return callNative(() => this.native.readDg(dgNumber));
+  }
+
+  /**
+   * Check the ATR captured at connect against the known Thai ID chips, without sending any APDU
+   * @returns True for a Thai national ID card
+   */
+  isThaiNationalId(): boolean {
+    return callNative(() => this.native.isThaiNationalId());
+  }
+
+  /**
+   * Read the citizen ID, names, date of birth and issue/expiry dates from a Thai national ID card
+   * Throws with code NOT_THAI_ID if the MOI applet can't be selected
+   *
+   * @param disconnectAfter Disconnect with this disposition once done, even if the read failed
+   * @returns Decoded, trimmed fields (names keep the card's '#' separators)
+   */
+  readThaiId(disconnectAfter?: Disposition): ThaiIdData {
+    return callNative(() => this.native.readThaiId(disconnectAfter));
+  }
+
+  /**
+   * Read the registered address from a Thai national ID card, split into its parts
+   * Throws with code NOT_THAI_ID if the MOI applet can't be selected
+   *
+   * @param disconnectAfter Disconnect with this disposition once done, even if the read failed
+   * @returns Address parts (empty strings for absent parts)
+   */
+  readThaiIdAddress(disconnectAfter?: Disposition): ThaiIdAddress {
+    return callNative(() => this.native.readThaiIdAddress(disconnectAfter));
+  }
+
+  /**
+   * Read the photo from a Thai national ID card
+   * Reads the 20 photo blocks in order and stops early if the card refuses one
+   *
+   * @param disconnectAfter Disconnect with this disposition once done, even if the read failed
+   * @param onProgress Called after each block with the number of blocks read so far
+   * @returns JPEG bytes, ready to write to disk
+   */
+  readThaiIdPhoto(
+    disconnectAfter?: Disposition,
+    onProgress?: (blocksRead: number, totalBlocks: number) => void
+  ): Buffer {
+    return callNative(() => this.native.readThaiIdPhoto(disconnectAfter, onProgress));
+  }
+
+  /**
+   * Read the personal data, address and photo in one transaction, without blocking the event loop
+   * Fails as a whole (e.g. with code CARD_REMOVED) if any part can't be read
+   *
+   * @param disconnectAfter Disconnect with this disposition once done, even if the read failed
+   * @param onProgress Called as photo blocks arrive with the number of blocks read so far
+   * @returns Promise of all the card's data
+   */
+  async readThaiIdAllAsync(
+    disconnectAfter?: Disposition,
+    onProgress?: (blocksRead: number, totalBlocks: number) => void
+  ): Promise<ThaiIdCard> {
+    return callNativeAsync(() => this.native.readThaiIdAllAsync(disconnectAfter, onProgress));
+  }
+
+  /**
+   * Read the photo from a Thai national ID card as a data URI, ready for an `<img src>`
+   * Same read as readThaiIdPhoto(); prefer that one when the raw bytes are needed
+   *
+   * @param disconnectAfter Disconnect with this disposition once done, even if the read failed
+   * @param onProgress Called after each block with the number of blocks read so far
+   * @returns `data:image/jpeg;base64,...` string
+   */
+  readThaiIdPhotoDataUri(
+    disconnectAfter?: Disposition,
+    onProgress?: (blocksRead: number, totalBlocks: number) => void
+  ): string {
+    return callNative(() => this.native.readThaiIdPhotoDataUri(disconnectAfter, onProgress));
+  }
+
+  /**
+   * Read a signed data block from a Thai ID card for offline verification
+   * The signature location isn't published and differs between chips, so it must be supplied
+   *
+   * @param layout Offsets and lengths of the data, signature and optional certificate
+   * @param disconnectAfter Disconnect with this disposition once done, even if the read failed
+   * @returns Raw data, signature and certificate (verification is up to the caller)
+   */
+  readThaiSignedData(layout: SignedBlockLayout, disconnectAfter?: Disposition): SignedBlock {
+    return callNative(() => this.native.readThaiSignedData(layout, disconnectAfter));
   }
 
   /**
@@ -424,23 +1448,94 @@ export class Card {
    * @param responseLength Expected response length (default: 40)
    * @param maxRetries Maximum retry attempts (default: 3)
    * @param retryDelayMs Delay between retries in milliseconds (default: 100)
+   * @param retryableStatusWords Status words worth another attempt, e.g. [0x6F00]; any other status is
+   *   returned at once (default: retry everything except 90 00 and 61xx)
    * @returns Transmit result with data and status word
+   * @throws code CARD_REMOVED or CARD_RESET at once, without further attempts
    */
   transmitWithRetry(
     command: Buffer,
     responseLength: number = 40,
     maxRetries?: number,
-    retryDelayMs?: number
+    retryDelayMs?: number,
+    retryableStatusWords?: number[]
   ): TransmitResult {
-    return this.native.transmitWithRetry(command, responseLength, maxRetries, retryDelayMs);
+    return callNative(() => this.native.transmitWithRetry(command, responseLength, maxRetries, retryDelayMs, retryableStatusWords));
+  }
+
+  /**
+   * Hold exclusive access to the card across several calls (SCardBeginTransaction)
+   * Other clients block until endTransaction() is called, so always end it (e.g. in a finally block)
+   * High-level reads made in between join this transaction
+   */
+  beginTransaction(): void {
+    callNative(() => this.native.beginTransaction());
+  }
+
+  /**
+   * End the transaction started by beginTransaction()
+   * Throws with code NOT_TRANSACTED if no transaction is open or it was lost (e.g. card reset)
+   * @param disposition Disposition mode (default: LeaveCard)
+   */
+  endTransaction(disposition: Disposition = Disposition.LeaveCard): void {
+    callNative(() => this.native.endTransaction(disposition));
+  }
+
+  /**
+   * Run a callback inside a transaction, ending it even if the callback throws
+   * The callback must be synchronous (a returned promise is not awaited before the transaction ends)
+   *
+   * @param callback Work to do while other clients are locked out
+   * @param disposition Disposition for the implicit endTransaction (default: LeaveCard)
+   * @returns What the callback returned
+   */
+  withTransaction<T>(callback: () => T, disposition: Disposition = Disposition.LeaveCard): T {
+    return callNative(() => this.native.withTransaction(callback, disposition)) as T;
+  }
+
+  /**
+   * Re-establish the connection on the same handle (SCardReconnect)
+   * Recovers from CARD_RESET errors without listing readers and connecting again; refreshes the ATR
+   *
+   * @param shareMode Share mode (default: Shared)
+   * @param preferredProtocol Preferred protocol (default: Any)
+   * @param disposition What to do with the card first (default: LeaveCard)
+   */
+  reconnect(
+    shareMode: ShareMode = ShareMode.Shared,
+    preferredProtocol?: Protocol,
+    disposition: Disposition = Disposition.LeaveCard
+  ): void {
+    callNative(() => this.native.reconnect(shareMode, preferredProtocol, disposition));
+  }
+
+  /**
+   * Reset the card and reconnect with the current share mode and protocol; refreshes the ATR
+   * Ends any open transaction
+   *
+   * @param cold Cycle the card's power (UnpowerCard) rather than a warm reset (ResetCard) (default: false)
+   */
+  reset(cold?: boolean): void {
+    callNative(() => this.native.reset(cold));
+  }
+
+  /**
+   * Power-cycle the card, keeping this Card usable
+   * PC/SC powers the card back up when reconnecting, so this is the same as reset(true);
+   * use disconnect(Disposition.UnpowerCard) to leave it off
+   */
+  powerOff(): void {
+    callNative(() => this.native.powerOff());
   }
 
   /**
    * Disconnect from card
+   * Later calls on this card throw with code NOT_CONNECTED; disconnecting twice is a no-op
+   * A card that is garbage-collected without being disconnected is released with LeaveCard
    * @param disposition Disposition mode (default: LeaveCard)
    */
   disconnect(disposition: Disposition = Disposition.LeaveCard): void {
-    this.native.disconnect(disposition);
+    callNative(() => this.native.disconnect(disposition));
   }
 }
 
@@ -449,5 +1544,175 @@ export class Card {
  * @returns Version string
  */
 export function getVersion(): string {
-  return binding.getVersion();
+  return callNative(() => binding.getVersion());
+}
+
+/**
+ * Convert a Buddhist-era YYYYMMDD date from a Thai ID card to an ISO YYYY-MM-DD date
+ * @param beDate Date as read from the card, e.g. '25180101'
+ * @returns ISO date, or undefined for malformed input or an unknown (00) month or day
+ */
+export function thaiDateToIso(beDate: string): string | undefined {
+  return callNative(() => binding.thaiDateToIso(beDate)) ?? undefined;
+}
+
+/**
+ * Library, PC/SC and platform details worth including in bug reports
+ * @returns Crate version, pcsc crate version, PC/SC backend and platform
+ */
+export function getEnvironmentInfo(): EnvironmentInfo {
+  return callNative(() => binding.getEnvironmentInfo());
+}
+
+/**
+ * Set a callback that receives log messages (e.g. dry-run APDUs)
+ * @param callback Log callback, or null to remove it
+ */
+export function setLogger(callback: ((message: string) => void) | null): void {
+  callNative(() => binding.setLogger(callback));
+}
+
+/**
+ * Log every APDU exchange (command, response, status word, elapsed time) through setLogger
+ * Off by default because APDUs can carry personal data; PINs and keys are always hidden
+ * @param enabled Debug logging enabled
+ */
+export function setDebug(enabled: boolean): void {
+  callNative(() => binding.setDebug(enabled));
+}
+
+/**
+ * Build the platform IOCTL value for a reader control function (SCARD_CTL_CODE)
+ * @param code Function code (e.g. 3400 for CM_IOCTL_GET_FEATURE_REQUEST)
+ * @returns Control code for Card.control()
+ */
+export function ctlCode(code: number): number {
+  return callNative(() => binding.ctlCode(code));
+}
+
+/**
+ * Build a command APDU with the right ISO 7816 case (1-4) and length encoding
+ * Short Lc/Le are used when possible, extended ones when data > 255 bytes or Le > 256
+ *
+ * @param cla Class byte
+ * @param ins Instruction byte
+ * @param p1 Parameter 1
+ * @param p2 Parameter 2
+ * @param data Command data (max 65535 bytes)
+ * @param le Expected response length (0 or 256 = up to 256 bytes, max 65536)
+ * @returns APDU ready for transmit()
+ */
+export function buildApdu(cla: number, ins: number, p1: number, p2: number, data?: Buffer, le?: number): Buffer {
+  return callNative(() => binding.buildApdu(cla, ins, p1, p2, data, le));
+}
+
+/**
+ * Compare two ATRs
+ * @param a First ATR
+ * @param b Second ATR
+ * @param ignoreHistorical Ignore historical bytes (and TCK), e.g. cards with a counter in them (default: false)
+ * @returns True if the ATRs match
+ */
+export function atrEqual(a: Buffer, b: Buffer, ignoreHistorical: boolean = false): boolean {
+  return callNative(() => binding.atrEqual(a, b, ignoreHistorical));
+}
+
+/**
+ * Split an ATR into its parts
+ * @param atr Card ATR
+ * @returns Convention, T0, indicated protocols, historical bytes and Thai ID generation
+ */
+export function parseAtr(atr: Buffer): AtrInfo {
+  return callNative(() => binding.parseAtr(atr));
+}
+
+/**
+ * Detect the Thai ID chip generation from the ATR
+ * @param atr Card ATR
+ * @returns Generation number, or undefined for unknown cards
+ */
+export function thaiIdGeneration(atr: Buffer): number | undefined {
+  return callNative(() => binding.thaiIdGeneration(atr)) ?? undefined;
+}
+
+/**
+ * Check an ATR against a list of known ATRs or ATR prefixes
+ * @param atr Card ATR
+ * @param known ATRs or prefixes to match
+ * @returns True if the ATR starts with any of them
+ */
+export function matchAtr(atr: Buffer, known: Buffer[]): boolean {
+  return callNative(() => binding.matchAtr(atr, known));
+}
+
+/**
+ * Describe an ISO 7816-4 status word
+ * @param sw1 Status word byte 1
+ * @param sw2 Status word byte 2
+ * @returns Human-readable description, or "Unknown status XXYY"
+ */
+export function describeStatus(sw1: number, sw2: number): string {
+  return callNative(() => binding.describeStatus(sw1, sw2));
+}
+
+/**
+ * Check a Thai citizen ID against its check digit
+ * @param cid Citizen ID
+ * @returns false unless it is exactly 13 digits with a valid check digit
+ */
+export function validateThaiCid(cid: string): boolean {
+  return callNative(() => binding.validateThaiCid(cid));
+}
+
+/**
+ * Serialize a readThaiIdAllAsync() result as JSON with a stable shape, for audit logs or API responses
+ * Fields are snake_case: cid, name_th and name_en (each { title, first, middle, last }),
+ * birth_date/issue_date/expire_date (ISO, null when unknown), address (house_no ... changwat)
+ * and photo (base64 JPEG)
+ * @param card Result of readThaiIdAllAsync()
+ * @param pretty Indent the output (default: false)
+ * @returns JSON text
+ */
+export function thaiIdToJson(card: ThaiIdCard, pretty?: boolean): string {
+  return callNative(() => binding.thaiIdToJson(card, pretty));
+}
+
+/**
+ * Encode bytes as hex
+ * @param data Bytes to encode
+ * @param spaced Put a space between bytes (default: false)
+ * @param uppercase Use A-F rather than a-f (default: true)
+ * @returns Hex string, e.g. "3B 67 00 00"
+ */
+export function toHex(data: Buffer, spaced?: boolean, uppercase?: boolean): string {
+  return callNative(() => binding.toHex(data, spaced, uppercase));
+}
+
+/**
+ * Decode a hex string, in either case; whitespace between digits is ignored
+ * Throws with code INVALID_ARGUMENT on a non-hex character or an odd number of digits
+ * @param hex Hex string, e.g. "00A40400" or "00 a4 04 00"
+ * @returns Decoded bytes
+ */
+export function fromHex(hex: string): Buffer {
+  return callNative(() => binding.fromHex(hex));
+}
+
+/**
+ * Decode TIS-620 text (Thai ID card fields) to a string
+ * Trailing space padding is trimmed; '#' name separators are kept
+ * @param bytes Raw field bytes
+ * @returns Decoded text
+ */
+export function decodeTis620(bytes: Buffer): string {
+  return callNative(() => binding.decodeTis620(bytes));
+}
+
+/**
+ * Decode TIS-620 text and split it on the '#' separators
+ * @param bytes Raw field bytes (e.g. a name: title#first#middle#last)
+ * @returns Trimmed parts
+ */
+export function decodeTis620Fields(bytes: Buffer): string[] {
+  return callNative(() => binding.decodeTis620Fields(bytes));
 }
@@ -1,30 +1,336 @@
-use crate::types::{CardStatus, TransmitResult};
+use crate::connection::Connection;
+use crate::error::{error, has_reason, pcsc_error, ATTRIBUTE_FAILED, ATTRIBUTE_UNSUPPORTED, CARD_REMOVED, CARD_RESET, COMMAND_FAILED, CONTROL_FAILED, INVALID_APDU, INVALID_ARGUMENT, INVALID_RESPONSE, LOCK_FAILED, NOT_CONNECTED, NOT_TRANSACTED, RECONNECT_FAILED, STATUS_FAILED, TIMEOUT, TRANSACTION_FAILED, TRANSMIT_FAILED};
+use crate::logger;
+use crate::throttle::Throttle;
+use crate::timeout::with_thread_timeout;
+use crate::types::{CardStatus, FullCardStatus, Protocol, ProtocolParams, ShareMode, TransmitResult};
+use crate::utils::{hex, parse_hex, ta1};
 use napi::bindgen_prelude::*;
+use napi::{JsFunction, JsUnknown};
 use napi_derive::napi;
 use pcsc::State;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant};
+
+/// Maximum data field of a short APDU
+const MAX_SHORT_DATA: u32 = 255;
+
+/// How many times a response buffer that was too small is doubled before giving up
+const RESPONSE_BUFFER_RETRIES: u32 = 2;
+
+/// Data length assumed when the reader doesn't report its maximum IFSD
+const DEFAULT_MAX_APDU_LENGTH: u32 = 255;
+
+/// Response buffer for internal SELECTs, large enough for verbose FCIs (61xx is followed up)
+pub(crate) const SELECT_RESPONSE_LENGTH: u32 = 256;
 
 #[napi]
 pub struct Card {
-    pub(crate) inner: Arc<Mutex<pcsc::Card>>,
+    pub(crate) inner: Arc<Mutex<Connection>>,
     pub(crate) atr: Option<Buffer>,
+    pub(crate) dry_run: bool,
+    /// Check Lc against the command length before sending
+    pub(crate) validate_apdus: bool,
+    /// CLA of the GET RESPONSE sent after 61xx
+    pub(crate) get_response_cla: u8,
+    /// Protocol negotiated at connect/reconnect; under T=0 the Le of case-4 commands is dropped
+    pub(crate) protocol: Option<pcsc::Protocol>,
+    /// Share mode of the last connect/reconnect, reused by reset and power_off
+    pub(crate) share_mode: pcsc::ShareMode,
+    pub(crate) throttle: Arc<Throttle>,
+    /// Set between begin_transaction and end_transaction; shared with worker clones, like `inner`
+    pub(crate) transaction_open: Arc<AtomicBool>,
+    /// Timed-out exchanges still holding `inner`; while any are, lock() fails fast instead of waiting
+    pub(crate) stalled: Arc<AtomicUsize>,
+}
+
+impl Card {
+    pub(crate) fn new(card: pcsc::Card, atr: Option<Buffer>, share_mode: pcsc::ShareMode, throttle: Arc<Throttle>) -> Self {
+        Self {
+            protocol: active_protocol(&card),
+            share_mode,
+            inner: Arc::new(Mutex::new(Connection::new(Some(card)))),
+            atr,
+            dry_run: false,
+            validate_apdus: true,
+            get_response_cla: 0x00,
+            throttle,
+            transaction_open: Arc::default(),
+            stalled: Arc::default(),
+        }
+    }
+
+    pub(crate) fn lock(&self) -> Result<MutexGuard<'_, Connection>> {
+        if self.stalled.load(Ordering::SeqCst) > 0 {
+            // A timed-out transmit may never return; don't queue up behind it
+            return match self.inner.try_lock() {
+                Ok(guard) => Ok(guard),
+                Err(TryLockError::WouldBlock) => {
+                    Err(error(TIMEOUT, "An earlier exchange timed out and is still waiting for the reader"))
+                }
+                Err(TryLockError::Poisoned(e)) => Err(error(LOCK_FAILED, format!("Failed to lock card: {}", e))),
+            };
+        }
+        self.inner.lock()
+            .map_err(|e| error(LOCK_FAILED, format!("Failed to lock card: {}", e)))
+    }
+
+    /// The protocol in use, to reconnect with (any when none was negotiated)
+    fn current_protocols(&self) -> pcsc::Protocols {
+        to_protocols(self.protocol.map(protocol_code), self.share_mode)
+    }
+
+    /// SCardReconnect and refresh the state that depends on the connection
+    fn reconnect_handle(&mut self, share_mode: pcsc::ShareMode, protocols: pcsc::Protocols, disposition: pcsc::Disposition) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        
+        let mut card = self.lock()?;
+        let handle = card.handle_mut().ok_or_else(|| error(NOT_CONNECTED, "Card is not connected"))?;
+        handle.reconnect(share_mode, protocols, disposition)
+            .map_err(|e| pcsc_error(RECONNECT_FAILED, "Failed to reconnect to card", e))?;
+        let atr = card_atr(handle);
+        let protocol = active_protocol(handle);
+        drop(card);
+        
+        self.atr = atr;
+        self.protocol = protocol;
+        self.share_mode = share_mode;
+        self.transaction_open.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Run a high-level read inside a transaction, then disconnect with `disconnect_after` if given
+    /// (even when the read failed)
+    ///
+    /// Inside an explicit begin_transaction/end_transaction the read joins that transaction.
+    pub(crate) fn read_then_release<T>(&self, disconnect_after: Option<u32>, read: impl FnOnce(Option<&pcsc::Card>) -> Result<T>) -> Result<T> {
+        let mut card = self.lock()?;
+        let result = if self.transaction_open.load(Ordering::SeqCst) {
+            read(card.handle())
+        } else {
+            in_transaction(&mut card, read)
+        };
+        if let Some(disposition) = disconnect_after {
+            card.release(to_disposition(disposition))?;
+            self.transaction_open.store(false, Ordering::SeqCst);
+        }
+        result
+    }
+
+    /// A handle sharing this card's connection and settings, to move onto a worker thread
+    pub(crate) fn worker(&self) -> Card {
+        Card {
+            inner: self.inner.clone(),
+            atr: self.atr.clone(),
+            dry_run: self.dry_run,
+            validate_apdus: self.validate_apdus,
+            get_response_cla: self.get_response_cla,
+            protocol: self.protocol,
+            share_mode: self.share_mode,
+            throttle: self.throttle.clone(),
+            transaction_open: self.transaction_open.clone(),
+            stalled: self.stalled.clone(),
+        }
+    }
+
+    /// Transmit through the card handle, or only log the command in dry-run mode
+    ///
+    /// Under T=0 a short case-4 command goes out without its Le; the card answers 61xx and
+    /// the data comes back through GET RESPONSE.
+    pub(crate) fn exchange(&self, card: Option<&pcsc::Card>, command: &[u8], response_length: u32, max_get_response: u32) -> Result<TransmitResult> {
+        self.exchange_redacted(card, command, response_length, max_get_response, command.len())
+    }
+
+    /// exchange, logging only the first `shown` bytes of the command (e.g. to keep a PIN out of the log)
+    pub(crate) fn exchange_redacted(&self, card: Option<&pcsc::Card>, command: &[u8], response_length: u32, max_get_response: u32, shown: usize) -> Result<TransmitResult> {
+        self.send(card, command, response_length, max_get_response, shown, 0x00)
+    }
+
+    /// exchange with a GET RESPONSE P2 other than 00 (newer Thai ID chips want 01)
+    pub(crate) fn exchange_with_get_response_p2(&self, card: Option<&pcsc::Card>, command: &[u8], response_length: u32, max_get_response: u32, get_response_p2: u8) -> Result<TransmitResult> {
+        self.send(card, command, response_length, max_get_response, command.len(), get_response_p2)
+    }
+
+    /// Body of exchange_redacted and exchange_with_get_response_p2
+    fn send(&self, card: Option<&pcsc::Card>, command: &[u8], response_length: u32, max_get_response: u32, shown: usize, get_response_p2: u8) -> Result<TransmitResult> {
+        let logged = || match command.get(..shown) {
+            Some(visible) if shown < command.len() => format!("{} ({} bytes hidden)", hex(visible), command.len() - shown),
+            _ => hex(command),
+        };
+        if self.validate_apdus {
+            validate_apdu(command)?;
+        }
+        if self.dry_run {
+            logger::log(format!("[dry-run] > {}", logged()));
+            return Ok(TransmitResult {
+                data: Buffer::from(Vec::new()),
+                sw1: 0x90,
+                sw2: 0x00,
+                status_word: 0x9000,
+                is_success: true,
+                get_response_iterations: None,
+            });
+        }
+        
+        self.throttle.wait();
+        let started = Instant::now();
+        let card = connected(card)?;
+        // The buffer fits the caller's hint or the command's own Le, whichever is larger
+        let transmit = |cmd: &[u8]| transmit_raw(card, cmd, response_length.max(expected_length(cmd)));
+        let result = self.transmit_for_protocol(transmit, command, max_get_response, get_response_p2);
+        self.throttle.record(&result);
+        if logger::debug_enabled() {
+            let outcome = match &result {
+                Ok(result) => format!("{} {:04X}", hex(&result.data), result.status_word),
+                Err(e) => e.reason.clone(),
+            };
+            logger::log(format!("> {} < {} ({} ms)", logged(), outcome, started.elapsed().as_millis()));
+        }
+        result
+    }
+
+    /// transmit_apdu with the command shaped for the negotiated protocol: under T=0 a case-4
+    /// command goes without its Le, and the data comes back through 61xx/GET RESPONSE
+    fn transmit_for_protocol(&self, transmit: impl Fn(&[u8]) -> pcsc::Result<Vec<u8>>, command: &[u8], max_get_response: u32, get_response_p2: u8) -> Result<TransmitResult> {
+        let command = match self.protocol {
+            Some(pcsc::Protocol::T0) => without_case4_le(command),
+            _ => command,
+        };
+        transmit_apdu(transmit, command, max_get_response, [self.get_response_cla, get_response_p2])
+    }
+
+    /// SCardControl on the locked handle; the inner result is the reader's own answer, so callers
+    /// can treat failures like an unsupported IOCTL themselves
+    pub(crate) fn send_control(&self, control_code: u32, data: &[u8]) -> Result<std::result::Result<Vec<u8>, pcsc::Error>> {
+        if self.dry_run {
+            logger::log(format!("[dry-run] control {:08X} > {}", control_code, hex(data)));
+            return Ok(Ok(Vec::new()));
+        }
+        
+        let card = self.lock()?;
+        let card = connected(card.handle())?;
+        let mut response = vec![0u8; pcsc::MAX_BUFFER_SIZE_EXTENDED];
+        Ok(card.control(control_code, data, &mut response).map(|response| response.to_vec()))
+    }
+
+    /// exchange on a worker thread, failing with TIMEOUT if it takes longer than `timeout`
+    pub(crate) fn exchange_with_timeout(&self, command: Vec<u8>, response_length: u32, max_get_response: u32, timeout: Duration) -> Result<TransmitResult> {
+        self.with_worker_timeout(move |card| {
+            let connection = card.lock()?;
+            card.exchange(connection.handle(), &command, response_length, max_get_response)
+        }, timeout)
+    }
+
+    /// Run `op` on a worker clone of this card, failing with TIMEOUT if it takes longer than `timeout`
+    ///
+    /// A timed-out transmit can't be aborted and keeps the card locked until the reader
+    /// returns. Until then the card counts as stalled, so other calls fail with TIMEOUT at
+    /// once rather than blocking on the lock.
+    fn with_worker_timeout<T, F>(&self, op: F, timeout: Duration) -> Result<T>
+    where
+        F: FnOnce(&Card) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let card = self.worker();
+        // Set by whichever of the worker and this caller is done first; the other one sees it
+        let settled = Arc::new(AtomicBool::new(false));
+        let worker_settled = settled.clone();
+        let result = with_thread_timeout(move || {
+            let result = op(&card);
+            if worker_settled.swap(true, Ordering::SeqCst) {
+                // The caller gave up and counted this worker as stalled
+                card.stalled.fetch_sub(1, Ordering::SeqCst);
+            }
+            result
+        }, timeout);
+        
+        if result.is_err() {
+            self.stalled.fetch_add(1, Ordering::SeqCst);
+            if settled.swap(true, Ordering::SeqCst) {
+                // The worker had already finished
+                self.stalled.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+        result
+    }
 }
 
 #[napi]
 impl Card {
-    #[napi]
+    /// Create a card that isn't attached to any reader; every transmit is logged and answered with 90 00
+    #[napi(factory, catch_unwind)]
+    pub fn dry_run() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Connection::new(None))),
+            atr: None,
+            dry_run: true,
+            validate_apdus: true,
+            get_response_cla: 0x00,
+            protocol: None,
+            share_mode: pcsc::ShareMode::Shared,
+            throttle: Arc::default(),
+            transaction_open: Arc::default(),
+            stalled: Arc::default(),
+        }
+    }
+
+    /// Log commands instead of sending them, answering each with a synthetic 90 00
+    #[napi(catch_unwind)]
+    pub fn set_dry_run(&mut self, enabled: bool) {
+        self.dry_run = enabled;
+    }
+
+    /// Check each short interindustry command's Lc against its length before sending (on by default)
+    ///
+    /// A mismatch fails with INVALID_APDU instead of reaching the card. Extended-length
+    /// commands and proprietary classes (CLA 80-FF) aren't checked.
+    #[napi(catch_unwind)]
+    pub fn set_validate_apdus(&mut self, enabled: bool) {
+        self.validate_apdus = enabled;
+    }
+
+    /// CLA byte for the GET RESPONSE issued after 61xx (default 0x00; some proprietary applets want 0x80)
+    #[napi(catch_unwind)]
+    pub fn set_get_response_cla(&mut self, cla: u8) {
+        self.get_response_cla = cla;
+    }
+
+    /// ATR captured at connect and refreshed by reconnect/reset/power_off; read from the card
+    /// if none was captured, and unset once the card is disconnected
+    #[napi(catch_unwind)]
     pub fn get_atr(&self) -> Option<Buffer> {
-        self.atr.clone()
+        if self.dry_run {
+            return self.atr.clone();
+        }
+        let card = self.lock().ok()?;
+        let handle = card.handle()?;
+        self.atr.clone().or_else(|| card_atr(handle))
     }
 
-    #[napi]
+    /// Read the ATR from the card again (SCardStatus) and store it for get_atr
+    #[napi(catch_unwind)]
+    pub fn refresh_atr(&mut self) -> Result<Option<Buffer>> {
+        if self.dry_run {
+            return Ok(self.atr.clone());
+        }
+        let card = self.lock()?;
+        let atr = card_atr(connected(card.handle())?);
+        drop(card);
+        
+        self.atr = atr.clone();
+        Ok(atr)
+    }
+
+    #[napi(catch_unwind)]
     pub fn get_status(&self) -> Result<CardStatus> {
-        let card = self.inner.lock()
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to lock card: {}", e)))?;
+        let card = self.lock()?;
+        let card = connected(card.handle())?;
         
         let card_status = card.status2_owned()
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to get card status: {:?}", e)))?;
+            .map_err(|e| pcsc_error(STATUS_FAILED, "Failed to get card status", e))?;
         
         let status = card_status.status();
         let atr = if card_status.atr().is_empty() {
@@ -37,127 +343,963 @@ impl Card {
             empty: (status.bits() & State::EMPTY.bits()) != 0,
             mute: (status.bits() & State::MUTE.bits()) != 0,
             atr,
+            change_count: None,
+            in_use: None,
+            exclusive: None,
+            unpowered: None,
         })
     }
 
-    #[napi]
-    pub fn transmit(&self, command: Buffer, response_length: u32, max_get_response: Option<u32>) -> Result<TransmitResult> {
-        let card = self.inner.lock()
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to lock card: {}", e)))?;
+    /// Everything SCardStatus reports: reader names, card state flags, protocol and ATR
+    #[napi(catch_unwind)]
+    pub fn get_full_status(&self) -> Result<FullCardStatus> {
+        let card = self.lock()?;
+        let card = connected(card.handle())?;
         
-        let cmd = command.as_ref();
-        let mut response = vec![0u8; response_length as usize + 2];
+        let card_status = card.status2_owned()
+            .map_err(|e| pcsc_error(STATUS_FAILED, "Failed to get card status", e))?;
         
-        let response_data = card.transmit(cmd, &mut response)
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to transmit APDU: {}", e)))?;
-        let response_len = response_data.len();
+        let status = card_status.status();
+        Ok(FullCardStatus {
+            reader_names: card_status.reader_names().iter().map(|name| name.to_string_lossy().to_string()).collect(),
+            absent: status.contains(pcsc::Status::ABSENT),
+            present: status.contains(pcsc::Status::PRESENT),
+            swallowed: status.contains(pcsc::Status::SWALLOWED),
+            powered: status.contains(pcsc::Status::POWERED),
+            negotiable: status.contains(pcsc::Status::NEGOTIABLE),
+            specific: status.contains(pcsc::Status::SPECIFIC),
+            protocol: card_status.protocol2().map(protocol_code),
+            atr: Some(card_status.atr()).filter(|atr| !atr.is_empty()).map(|atr| Buffer::from(atr.to_vec())),
+        })
+    }
+
+    /// Share mode obtained at connect (or the last reconnect/reset)
+    #[napi(catch_unwind)]
+    pub fn get_share_mode(&self) -> ShareMode {
+        share_mode_code(self.share_mode)
+    }
+
+    /// Protocol the card negotiated at connect
+    ///
+    /// Undefined for a direct connection with no card protocol.
+    #[napi(catch_unwind)]
+    pub fn get_active_protocol(&self) -> Result<Option<Protocol>> {
+        let card = self.lock()?;
+        let card = connected(card.handle())?;
         
-        let sw1 = if response_len >= 2 { response[response_len - 2] } else { 0 };
-        let sw2 = if response_len >= 1 { response[response_len - 1] } else { 0 };
+        let card_status = card.status2_owned()
+            .map_err(|e| pcsc_error(STATUS_FAILED, "Failed to get card status", e))?;
+        Ok(card_status.protocol2().map(protocol_code))
+    }
+
+    /// Negotiated protocol and the FI/DI indices from the ATR's TA1 (1/1 when TA1 is absent)
+    #[napi(catch_unwind)]
+    pub fn protocol_parameters(&self) -> Result<ProtocolParams> {
+        let card = self.lock()?;
+        let card = connected(card.handle())?;
         
-        let mut data = if response_len >= 2 {
-            let data_end = response_len - 2;
-            response[..data_end].to_vec()
-        } else {
-            vec![]
+        let card_status = card.status2_owned()
+            .map_err(|e| pcsc_error(STATUS_FAILED, "Failed to get card status", e))?;
+        let (fi, di) = match ta1(card_status.atr()) {
+            Some(ta1) => (ta1 >> 4, ta1 & 0x0F),
+            None => (1, 1),
         };
         
+        Ok(ProtocolParams {
+            protocol: card_status.protocol2().map(protocol_code),
+            fi,
+            di,
+            current_f: attribute_u32(card, pcsc::Attribute::CurrentF),
+            current_d: attribute_u32(card, pcsc::Attribute::CurrentD),
+        })
+    }
+
+    /// Largest information field the reader accepts (SCARD_ATTR_MAX_IFSD), for sizing
+    /// READ BINARY chunks and command chaining
+    ///
+    /// Falls back to 255 when the reader doesn't report it.
+    #[napi(catch_unwind)]
+    pub fn get_max_apdu_length(&self) -> Result<u32> {
+        if self.dry_run {
+            return Ok(DEFAULT_MAX_APDU_LENGTH);
+        }
+        let card = self.lock()?;
+        let card = connected(card.handle())?;
+        Ok(attribute_u32(card, pcsc::Attribute::MaxIfsd)
+            .filter(|length| *length > 0)
+            .unwrap_or(DEFAULT_MAX_APDU_LENGTH))
+    }
+
+    /// Send an APDU; with `timeout_ms`, give up with TIMEOUT if the card doesn't answer in time
+    ///
+    /// The receive buffer is at least as large as the command's Le, with a short Le of 00 read
+    /// as 256 bytes and an extended Le of 00 00 as 65536.
+    #[napi(catch_unwind)]
+    pub fn transmit(&self, command: Buffer, response_length: u32, max_get_response: Option<u32>, timeout_ms: Option<u32>) -> Result<TransmitResult> {
         let max_get_response = max_get_response.unwrap_or(3);
-        if sw1 == 0x61 && max_get_response > 0 {
-            let mut remaining = sw2 as usize;
-            let mut get_response_count = 0;
-            
-            while remaining > 0 && get_response_count < max_get_response {
-                let get_response_cmd = vec![0x00, 0xC0, 0x00, 0x00, remaining.min(0xFF) as u8];
-                let mut get_response = vec![0u8; remaining.min(0xFF) + 2];
-                
-                let get_response_len: usize = match card.transmit(&get_response_cmd, &mut get_response) {
-                    Ok(data) => data.len(),
-                    Err(_) => break,
-                };
-                
-                if get_response_len >= 2 {
-                    let get_sw1 = get_response[get_response_len - 2];
-                    let get_sw2 = get_response[get_response_len - 1];
-                    
-                    if get_sw1 == 0x90 && get_sw2 == 0x00 {
-                        let data_len = get_response_len - 2;
-                        if data_len > 0 {
-                            let get_data = &get_response[..data_len];
-                            data.extend_from_slice(get_data);
-                        }
-                        break;
-                    } else if get_sw1 == 0x61 {
-                        let data_len = get_response_len - 2;
-                        if data_len > 0 {
-                            let get_data = &get_response[..data_len];
-                            data.extend_from_slice(get_data);
-                        }
-                        remaining = get_sw2 as usize;
-                        get_response_count += 1;
-                    } else {
-                        break;
-                    }
-                } else {
+        if let Some(timeout_ms) = timeout_ms {
+            return self.exchange_with_timeout(command.to_vec(), response_length, max_get_response, Duration::from_millis(timeout_ms as u64));
+        }
+        
+        let card = self.lock()?;
+        self.exchange(card.handle(), command.as_ref(), response_length, max_get_response)
+    }
+
+    /// Send several APDUs under a single lock, optionally inside one transaction
+    ///
+    /// Each command gets the same GET RESPONSE / 6Cxx handling as transmit. With `stop_on_error`,
+    /// the batch stops after the first result that isn't 90 00 (that result is included).
+    #[napi(catch_unwind)]
+    pub fn transmit_batch(&self, commands: Vec<Buffer>, response_length: u32, use_transaction: Option<bool>, stop_on_error: Option<bool>) -> Result<Vec<TransmitResult>> {
+        let stop_on_error = stop_on_error.unwrap_or(false);
+        let run = |card: Option<&pcsc::Card>| {
+            let mut results = Vec::with_capacity(commands.len());
+            for command in &commands {
+                let result = self.exchange(card, command, response_length, 3)?;
+                let failed = result.sw1 != 0x90 || result.sw2 != 0x00;
+                results.push(result);
+                if failed && stop_on_error {
                     break;
                 }
             }
-        }
+            Ok(results)
+        };
         
-        Ok(TransmitResult {
-            data: Buffer::from(data),
-            sw1,
-            sw2,
+        let mut card = self.lock()?;
+        if use_transaction.unwrap_or(false) && !self.transaction_open.load(Ordering::SeqCst) {
+            in_transaction(&mut card, run)
+        } else {
+            run(card.handle())
+        }
+    }
+
+    /// Send APDUs given as hex strings (e.g. "00A4040007A0000000031010"), in order, as transmit_batch
+    ///
+    /// Every string is parsed before anything is sent; a bad one fails with its index.
+    /// With `stop_on_error`, the script stops after the first result that isn't 90 00.
+    #[napi(catch_unwind)]
+    pub fn run_script(&self, apdus: Vec<String>, stop_on_error: Option<bool>) -> Result<Vec<TransmitResult>> {
+        let commands = apdus.iter().enumerate()
+            .map(|(index, apdu)| {
+                parse_hex(apdu)
+                    .filter(|command| command.len() >= 4)
+                    .map(Buffer::from)
+                    .ok_or_else(|| error(INVALID_ARGUMENT, format!("APDU {} is not a valid hex command: {:?}", index, apdu)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.transmit_batch(commands, MAX_SHORT_DATA + 1, None, stop_on_error)
+    }
+
+    /// Like transmit, but the PC/SC call runs on a worker thread and the result is a Promise
+    ///
+    /// Calls on the same card still serialize on its lock, in the order they acquire it.
+    #[napi(catch_unwind, ts_return_type = "Promise<TransmitResult>")]
+    pub fn transmit_async(&self, command: Buffer, response_length: u32, max_get_response: Option<u32>, timeout_ms: Option<u32>) -> AsyncTask<TransmitTask> {
+        AsyncTask::new(TransmitTask {
+            card: self.worker(),
+            command: command.to_vec(),
+            response_length,
+            max_get_response: max_get_response.unwrap_or(3),
+            timeout: timeout_ms.map(|timeout_ms| Duration::from_millis(timeout_ms as u64)),
         })
     }
 
-    #[napi]
+    /// Send a reader control command (SCardControl), e.g. a feature request or vendor escape
+    ///
+    /// Works on Direct connections with no card present. `control_code` is the platform
+    /// IOCTL value; see `ctlCode`.
+    #[napi(catch_unwind)]
+    pub fn control(&self, control_code: u32, data: Buffer) -> Result<Buffer> {
+        let response = self.send_control(control_code, &data)?
+            .map_err(|e| pcsc_error(CONTROL_FAILED, "Failed to send control command", e))?;
+        Ok(Buffer::from(response))
+    }
+
+    /// Read a reader/card attribute (SCardGetAttrib) by its SCARD_ATTR_* value
+    #[napi(catch_unwind)]
+    pub fn get_attribute(&self, attr_id: u32) -> Result<Buffer> {
+        let attribute = ATTRIBUTES.iter()
+            .find(|attribute| **attribute as u32 == attr_id)
+            .copied()
+            .ok_or_else(|| error(INVALID_ARGUMENT, format!("Unknown attribute {:08X}", attr_id)))?;
+        
+        let card = self.lock()?;
+        let card = connected(card.handle())?;
+        let read = || -> std::result::Result<Vec<u8>, pcsc::Error> {
+            let mut buffer = vec![0u8; card.get_attribute_len(attribute)?];
+            Ok(card.get_attribute(attribute, &mut buffer)?.to_vec())
+        };
+        match read() {
+            Ok(value) => Ok(Buffer::from(value)),
+            Err(pcsc::Error::UnsupportedFeature) => {
+                Err(error(ATTRIBUTE_UNSUPPORTED, format!("Reader does not support attribute {:08X}", attr_id)))
+            }
+            Err(e) => Err(pcsc_error(ATTRIBUTE_FAILED, "Failed to read attribute", e)),
+        }
+    }
+
+    /// Largest command data field sent in a single APDU (short APDUs)
+    #[napi(catch_unwind)]
+    pub fn max_command_data(&self) -> u32 {
+        MAX_SHORT_DATA
+    }
+
+    /// Send a command whose data may exceed one APDU, splitting it with ISO 7816 command chaining
+    ///
+    /// Every block but the last has the chaining bit (0x10) set in CLA. Returns the final block's
+    /// result, or the first intermediate result that isn't 90 00.
+    ///
+    /// Data that fits in one short APDU goes out unchained. Chaining works the same under T=0 and
+    /// T=1, so the active protocol isn't consulted; extended APDUs aren't used because T=0 can't
+    /// carry them and many cards don't accept them.
+    #[napi(catch_unwind)]
+    pub fn transmit_large(&self, cla: u8, ins: u8, p1: u8, p2: u8, data: Buffer, response_length: Option<u32>) -> Result<TransmitResult> {
+        let card = self.lock()?;
+        let chunk_size = self.max_command_data() as usize;
+        
+        let mut offset = 0;
+        loop {
+            let end = (offset + chunk_size).min(data.len());
+            let chained = end < data.len();
+            let chunk = &data[offset..end];
+            
+            let mut command = vec![if chained { cla | 0x10 } else { cla & !0x10 }, ins, p1, p2];
+            if !chunk.is_empty() {
+                command.push(chunk.len() as u8);
+                command.extend_from_slice(chunk);
+            }
+            let response_length = if chained { 0 } else { response_length.unwrap_or(256) };
+            let result = self.exchange(card.handle(), &command, response_length, 3)?;
+            if !chained || result.sw1 != 0x90 || result.sw2 != 0x00 {
+                return Ok(result);
+            }
+            offset = end;
+        }
+    }
+
+    /// Read a record (READ RECORD with Le=00; a 6Cxx answer is re-issued by transmit)
+    #[napi(catch_unwind)]
+    pub fn read_record(&self, record_number: u8, sfi: Option<u8>) -> Result<Buffer> {
+        let card = self.lock()?;
+        
+        let p2 = match sfi {
+            Some(sfi) => ((sfi & 0x1F) << 3) | 0x04,
+            None => 0x04,
+        };
+        let command = [0x00, 0xB2, record_number, p2, 0x00];
+        let result = self.exchange(card.handle(), &command, 256, 3)?;
+        
+        if result.sw1 != 0x90 || result.sw2 != 0x00 {
+            return Err(error(COMMAND_FAILED, format!("READ RECORD failed with status {:02X}{:02X}", result.sw1, result.sw2)));
+        }
+        Ok(result.data)
+    }
+
+    /// Send an APDU up to `max_retries` times (default 3), `retry_delay_ms` (default 100) apart
+    ///
+    /// By default any status other than 90 00 or 61xx is tried again; with
+    /// `retryable_status_words` only those (e.g. 0x6F00) are, and any other status is returned.
+    /// A removed or reset card fails at once.
+    #[napi(catch_unwind)]
     pub fn transmit_with_retry(
         &self,
         command: Buffer,
         response_length: u32,
         max_retries: Option<u32>,
         retry_delay_ms: Option<u32>,
+        retryable_status_words: Option<Vec<u32>>,
     ) -> Result<TransmitResult> {
         let max_retries = max_retries.unwrap_or(3);
         let retry_delay = Duration::from_millis(retry_delay_ms.unwrap_or(100) as u64);
-        
-        let mut last_error = None;
-        
         let cmd_vec = command.as_ref().to_vec();
         
-        for attempt in 0..max_retries {
-            match self.transmit(Buffer::from(cmd_vec.clone()), response_length, Some(3)) {
-                Ok(result) => {
-                    if result.sw1 == 0x90 && result.sw2 == 0x00 {
-                        return Ok(result);
-                    } else if result.sw1 == 0x61 {
-                        return Ok(result);
-                    } else if attempt < max_retries - 1 {
-                        std::thread::sleep(retry_delay);
-                        continue;
-                    } else {
-                        return Ok(result);
-                    }
-                }
-                Err(e) => {
-                    last_error = Some(e);
-                    if attempt < max_retries - 1 {
-                        std::thread::sleep(retry_delay);
-                    }
+        retry_transmit(
+            || self.transmit(Buffer::from(cmd_vec.clone()), response_length, Some(3), None),
+            max_retries,
+            retry_delay,
+            retryable_status_words.as_deref(),
+        )
+    }
+
+    /// Start a transaction that holds exclusive access until end_transaction
+    ///
+    /// Other clients block until it ends, so always pair it with end_transaction.
+    #[napi(catch_unwind)]
+    pub fn begin_transaction(&self) -> Result<()> {
+        if self.transaction_open.load(Ordering::SeqCst) {
+            return Err(error(TRANSACTION_FAILED, "A transaction is already in progress"));
+        }
+        if !self.dry_run {
+            // The transaction stays open in the shared connection until end_transaction ends it
+            self.lock()?.begin_transaction()?;
+        }
+        self.transaction_open.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// End the transaction started by begin_transaction, with the given disposition
+    #[napi(catch_unwind)]
+    pub fn end_transaction(&self, disposition: u32) -> Result<()> {
+        if !self.transaction_open.swap(false, Ordering::SeqCst) {
+            return Err(error(NOT_TRANSACTED, "No transaction in progress"));
+        }
+        if self.dry_run {
+            return Ok(());
+        }
+        self.lock()?.end_transaction(to_disposition(disposition))
+    }
+
+    /// Run `callback` inside a transaction and return what it returns
+    ///
+    /// The transaction is ended with `disposition` (default LeaveCard) even when the callback
+    /// throws; the callback's exception is then rethrown as is. The callback must finish its
+    /// work synchronously: a returned promise is not awaited.
+    #[napi(catch_unwind, ts_args_type = "callback: () => unknown, disposition?: number", ts_return_type = "unknown")]
+    pub fn with_transaction(&self, callback: JsFunction, disposition: Option<u32>) -> Result<JsUnknown> {
+        self.begin_transaction()?;
+        let result = callback.call_without_args(None);
+        // The transaction may already be gone if the callback ended it or reset the card
+        let ended = if self.transaction_open.load(Ordering::SeqCst) {
+            self.end_transaction(disposition.unwrap_or(0))
+        } else {
+            Ok(())
+        };
+        match (result, ended) {
+            (Ok(value), Ok(())) => Ok(value),
+            (Ok(_), Err(e)) => Err(e),
+            (Err(e), ended) => {
+                if let Err(end_error) = ended {
+                    logger::log(format!("Failed to end transaction after callback error: {}", end_error.reason));
                 }
+                Err(e)
             }
         }
-        
-        Err(last_error.unwrap_or_else(|| {
-            napi::Error::new(napi::Status::GenericFailure, "Failed to transmit APDU after retries".to_string())
-        }))
     }
 
-    #[napi]
+    /// Re-establish the connection on the same handle (SCardReconnect), e.g. after the card was reset
+    ///
+    /// Ends any open transaction and refreshes the stored ATR.
+    #[napi(catch_unwind)]
+    pub fn reconnect(&mut self, share_mode: ShareMode, preferred_protocols: Option<Protocol>, disposition: u32) -> Result<()> {
+        let share_mode = to_share_mode(share_mode);
+        self.reconnect_handle(share_mode, to_protocols(preferred_protocols, share_mode), to_disposition(disposition))
+    }
+
+    /// Reset the card and reconnect with the current share mode and protocol
+    ///
+    /// A warm reset (ResetCard) re-runs the ATR with the card powered; a cold reset
+    /// (UnpowerCard) cycles its power, which clears states a warm reset leaves behind.
+    /// Ends any open transaction and refreshes the stored ATR.
+    #[napi(catch_unwind)]
+    pub fn reset(&mut self, cold: Option<bool>) -> Result<()> {
+        let disposition = if cold.unwrap_or(false) {
+            pcsc::Disposition::UnpowerCard
+        } else {
+            pcsc::Disposition::ResetCard
+        };
+        self.reconnect_handle(self.share_mode, self.current_protocols(), disposition)
+    }
+
+    /// Power the card off, keeping the handle
+    ///
+    /// PC/SC powers the card back up as part of reconnecting, so this is a power cycle
+    /// (the same as a cold reset). To leave the card unpowered, disconnect with UnpowerCard.
+    #[napi(catch_unwind)]
+    pub fn power_off(&mut self) -> Result<()> {
+        self.reconnect_handle(self.share_mode, self.current_protocols(), pcsc::Disposition::UnpowerCard)
+    }
+
+    /// Disconnect with the given disposition; later calls fail with NOT_CONNECTED
+    #[napi(catch_unwind)]
     pub fn disconnect(&self, disposition: u32) -> Result<()> {
-        let _ = disposition;
+        let mut card = self.lock()?;
+        card.release(to_disposition(disposition))?;
+        self.transaction_open.store(false, Ordering::SeqCst);
         Ok(())
     }
 }
 
+/// The retry loop of transmit_with_retry around `send`
+fn retry_transmit(
+    mut send: impl FnMut() -> Result<TransmitResult>,
+    max_retries: u32,
+    retry_delay: Duration,
+    retryable_status_words: Option<&[u32]>,
+) -> Result<TransmitResult> {
+    let mut last_error = None;
+    
+    for attempt in 0..max_retries {
+        match send() {
+            Ok(result) => {
+                let retryable = match retryable_status_words {
+                    Some(status_words) => status_words.contains(&(result.status_word as u32)),
+                    // Anything but success or more data behind 61xx
+                    None => !result.is_success && result.sw1 != 0x61,
+                };
+                if !retryable || attempt + 1 >= max_retries {
+                    return Ok(result);
+                }
+                std::thread::sleep(retry_delay);
+            }
+            Err(e) => {
+                // Retrying won't bring a pulled card back, and a reset card needs a reconnect first
+                if has_reason(&e, CARD_REMOVED) || has_reason(&e, CARD_RESET) {
+                    return Err(e);
+                }
+                last_error = Some(e);
+                if attempt < max_retries - 1 {
+                    std::thread::sleep(retry_delay);
+                }
+            }
+        }
+    }
+    
+    Err(last_error.unwrap_or_else(|| {
+        error(TRANSMIT_FAILED, "Failed to transmit APDU after retries")
+    }))
+}
+
+/// Release a handle that wasn't disconnected explicitly with LeaveCard, rather than the
+/// reset pcsc would otherwise do, so other applications find the card as it was left
+///
+/// Worker clones share the handle, so only the last one to go disconnects it.
+impl Drop for Card {
+    fn drop(&mut self) {
+        let Some(inner) = Arc::get_mut(&mut self.inner) else { return };
+        let card = inner.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = card.release(pcsc::Disposition::LeaveCard) {
+            logger::log(format!("Failed to release card handle on drop: {}", e.reason));
+        }
+    }
+}
+
+/// Worker-thread half of transmit_async
+pub struct TransmitTask {
+    card: Card,
+    command: Vec<u8>,
+    response_length: u32,
+    max_get_response: u32,
+    timeout: Option<Duration>,
+}
+
+impl Task for TransmitTask {
+    type Output = TransmitResult;
+    type JsValue = TransmitResult;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        if let Some(timeout) = self.timeout {
+            return self.card.exchange_with_timeout(self.command.clone(), self.response_length, self.max_get_response, timeout);
+        }
+        let connection = self.card.lock()?;
+        self.card.exchange(connection.handle(), &self.command, self.response_length, self.max_get_response)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Attributes readable with `get_attribute`, looked up by their SCARD_ATTR_* value
+const ATTRIBUTES: &[pcsc::Attribute] = &[
+    pcsc::Attribute::VendorName,
+    pcsc::Attribute::VendorIfdType,
+    pcsc::Attribute::VendorIfdVersion,
+    pcsc::Attribute::VendorIfdSerialNo,
+    pcsc::Attribute::ChannelId,
+    pcsc::Attribute::AsyncProtocolTypes,
+    pcsc::Attribute::DefaultClk,
+    pcsc::Attribute::MaxClk,
+    pcsc::Attribute::DefaultDataRate,
+    pcsc::Attribute::MaxDataRate,
+    pcsc::Attribute::MaxIfsd,
+    pcsc::Attribute::SyncProtocolTypes,
+    pcsc::Attribute::PowerMgmtSupport,
+    pcsc::Attribute::Characteristics,
+    pcsc::Attribute::CurrentProtocolType,
+    pcsc::Attribute::CurrentClk,
+    pcsc::Attribute::CurrentF,
+    pcsc::Attribute::CurrentD,
+    pcsc::Attribute::CurrentN,
+    pcsc::Attribute::CurrentW,
+    pcsc::Attribute::CurrentIfsc,
+    pcsc::Attribute::CurrentIfsd,
+    pcsc::Attribute::CurrentBwt,
+    pcsc::Attribute::CurrentCwt,
+    pcsc::Attribute::CurrentEbcEncoding,
+    pcsc::Attribute::ExtendedBwt,
+    pcsc::Attribute::IccPresence,
+    pcsc::Attribute::IccInterfaceStatus,
+    pcsc::Attribute::CurrentIoState,
+    pcsc::Attribute::AtrString,
+    pcsc::Attribute::IccTypePerAtr,
+    pcsc::Attribute::Maxinput,
+    pcsc::Attribute::DeviceUnit,
+    pcsc::Attribute::DeviceInUse,
+    pcsc::Attribute::DeviceFriendlyName,
+    pcsc::Attribute::DeviceSystemName,
+];
+
+/// Protocol as exposed to JS
+pub(crate) fn protocol_code(protocol: pcsc::Protocol) -> Protocol {
+    match protocol {
+        pcsc::Protocol::T0 => Protocol::T0,
+        pcsc::Protocol::T1 => Protocol::T1,
+        pcsc::Protocol::RAW => Protocol::Raw,
+    }
+}
+
+/// Read a DWORD attribute, if the reader supports it
+fn attribute_u32(card: &pcsc::Card, attribute: pcsc::Attribute) -> Option<u32> {
+    let value = card.get_attribute_owned(attribute).ok()?;
+    let bytes: [u8; 4] = value.get(..4)?.try_into().ok()?;
+    Some(u32::from_le_bytes(bytes))
+}
+
+/// The protocol the card handle is using; None for a direct connection or when status fails
+fn active_protocol(card: &pcsc::Card) -> Option<pcsc::Protocol> {
+    card.status2_owned().ok()?.protocol2()
+}
+
+/// Map the share mode to pcsc
+pub(crate) fn to_share_mode(share_mode: ShareMode) -> pcsc::ShareMode {
+    match share_mode {
+        ShareMode::Shared => pcsc::ShareMode::Shared,
+        ShareMode::Exclusive => pcsc::ShareMode::Exclusive,
+        ShareMode::Direct => pcsc::ShareMode::Direct,
+    }
+}
+
+/// Map a pcsc share mode to the JS enum
+pub(crate) fn share_mode_code(share_mode: pcsc::ShareMode) -> ShareMode {
+    match share_mode {
+        pcsc::ShareMode::Shared => ShareMode::Shared,
+        pcsc::ShareMode::Exclusive => ShareMode::Exclusive,
+        pcsc::ShareMode::Direct => ShareMode::Direct,
+    }
+}
+
+/// Map the preferred protocol (left out: any, or none for a Direct connection)
+pub(crate) fn to_protocols(preferred_protocols: Option<Protocol>, share_mode: pcsc::ShareMode) -> pcsc::Protocols {
+    match preferred_protocols {
+        Some(Protocol::T0) => pcsc::Protocols::T0,
+        Some(Protocol::T1) => pcsc::Protocols::T1,
+        Some(Protocol::Raw) => pcsc::Protocols::RAW,
+        // Direct connections may have no card to negotiate with
+        None if matches!(share_mode, pcsc::ShareMode::Direct) => pcsc::Protocols::UNDEFINED,
+        _ => pcsc::Protocols::ANY,
+    }
+}
+
+/// The card's ATR; None when it can't be read (e.g. Direct connection to an empty slot)
+pub(crate) fn card_atr(card: &pcsc::Card) -> Option<Buffer> {
+    card.status2_owned().ok()
+        .map(|status| status.atr().to_vec())
+        .filter(|atr| !atr.is_empty())
+        .map(Buffer::from)
+}
+
+/// Map the numeric disposition used by the JS API (0 = LeaveCard, 1 = ResetCard, 2 = UnpowerCard, 3 = EjectCard)
+pub(crate) fn to_disposition(disposition: u32) -> pcsc::Disposition {
+    match disposition {
+        1 => pcsc::Disposition::ResetCard,
+        2 => pcsc::Disposition::UnpowerCard,
+        3 => pcsc::Disposition::EjectCard,
+        _ => pcsc::Disposition::LeaveCard,
+    }
+}
+
+/// Run `f` inside a PC/SC transaction (dry-run and disconnected cards run it directly)
+///
+/// If the transaction was lost before it ended (SCARD_E_NOT_TRANSACTED, e.g. the card was reset),
+/// the APDUs may still have completed, so this is logged rather than treated as a failure.
+pub(crate) fn in_transaction<T>(card: &mut Connection, f: impl FnOnce(Option<&pcsc::Card>) -> Result<T>) -> Result<T> {
+    let handle = match card.handle_mut() {
+        Some(handle) => handle,
+        None => return f(None),
+    };
+    
+    let transaction = handle.transaction()
+        .map_err(|e| pcsc_error(TRANSACTION_FAILED, "Failed to begin transaction", e))?;
+    let result = f(Some(&*transaction));
+    match transaction.end(pcsc::Disposition::LeaveCard) {
+        Ok(()) => {}
+        Err((_, pcsc::Error::NotTransacted)) => {
+            logger::log("Transaction was lost before it ended (card reset?)".to_string());
+        }
+        Err((_, e)) => {
+            if result.is_ok() {
+                return Err(pcsc_error(TRANSACTION_FAILED, "Failed to end transaction", e));
+            }
+        }
+    }
+    result
+}
+
+/// The card handle, unless it has been disconnected
+fn connected(card: Option<&pcsc::Card>) -> Result<&pcsc::Card> {
+    card.ok_or_else(|| error(NOT_CONNECTED, "Card is not connected"))
+}
+
+/// Single SCardTransmit, returning the raw response (data followed by SW1 SW2)
+///
+/// `response_length` is a hint: if the card has more data, the buffer is doubled and the
+/// command re-sent (up to RESPONSE_BUFFER_RETRIES times), so it must be safe to repeat.
+fn transmit_raw(card: &pcsc::Card, cmd: &[u8], response_length: u32) -> pcsc::Result<Vec<u8>> {
+    transmit_growing(|cmd, buffer| card.transmit(cmd, buffer).map(|response| response.len()), cmd, response_length)
+}
+
+/// transmit_raw around `transmit`, which fills the buffer and returns the response length
+fn transmit_growing(transmit: impl Fn(&[u8], &mut [u8]) -> pcsc::Result<usize>, cmd: &[u8], response_length: u32) -> pcsc::Result<Vec<u8>> {
+    let mut response = vec![0u8; response_length as usize + 2];
+    let mut retries = 0;
+    loop {
+        match transmit(cmd, &mut response) {
+            Ok(response_len) => {
+                response.truncate(response_len);
+                return Ok(response);
+            }
+            Err(pcsc::Error::InsufficientBuffer) if retries < RESPONSE_BUFFER_RETRIES && response.len() < pcsc::MAX_BUFFER_SIZE_EXTENDED => {
+                retries += 1;
+                let grown = (response.len() * 2).min(pcsc::MAX_BUFFER_SIZE_EXTENDED);
+                logger::log(format!("Response buffer too small ({} bytes), retrying with {}", response.len(), grown));
+                response.resize(grown, 0);
+            }
+            // The card was there at connect, so an empty slot now means it was pulled
+            Err(pcsc::Error::NoSmartcard) => return Err(pcsc::Error::RemovedCard),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// One exchange through `transmit`, split into (data, SW1, SW2)
+fn transmit_once(transmit: &impl Fn(&[u8]) -> pcsc::Result<Vec<u8>>, cmd: &[u8]) -> Result<(Vec<u8>, u8, u8)> {
+    let mut response = transmit(cmd).map_err(|e| pcsc_error(TRANSMIT_FAILED, "Failed to transmit APDU", e))?;
+    let [.., sw1, sw2] = response[..] else {
+        return Err(error(INVALID_RESPONSE, format!("Response too short for a status word: {} byte(s)", response.len())));
+    };
+    response.truncate(response.len() - 2);
+    Ok((response, sw1, sw2))
+}
+
+/// Check that a short interindustry command's length agrees with its Lc (case 3 or 4)
+fn validate_apdu(cmd: &[u8]) -> Result<()> {
+    match *cmd {
+        [] | [_] | [_, _] | [_, _, _] => Err(error(INVALID_APDU, format!("Command is {} byte(s), shorter than the 4-byte header", cmd.len()))),
+        // Proprietary class: the structure is up to the card
+        [cla, ..] if cla & 0x80 != 0 => Ok(()),
+        // Case 1, case 2, or an extended length field
+        [_, _, _, _] | [_, _, _, _, _] | [_, _, _, _, 0, ..] => Ok(()),
+        [_, _, _, _, lc, ..] => {
+            let data_len = cmd.len() - 5;
+            if data_len == lc as usize || data_len == lc as usize + 1 {
+                Ok(())
+            } else {
+                Err(error(INVALID_APDU, format!("Lc is {} but the command carries {} byte(s) after it", lc, data_len)))
+            }
+        }
+    }
+}
+
+/// Response length asked for by the command's Le field (0 when it has none)
+///
+/// Le = 00 means 256 bytes in a short APDU and 00 00 means 65536 in an extended one.
+fn expected_length(cmd: &[u8]) -> u32 {
+    let short = |le: u8| if le == 0 { 256 } else { le as u32 };
+    let extended = |le: &[u8]| match u16::from_be_bytes([le[0], le[1]]) {
+        0 => 65536,
+        le => le as u32,
+    };
+    match cmd.get(4..) {
+        // Case 2 short: header, Le
+        Some(&[le]) => short(le),
+        // Case 2 extended: header, 00, Le (2 bytes)
+        Some(&[0, hi, lo]) => extended(&[hi, lo]),
+        Some(&[lc, ..]) if lc != 0 && cmd.len() == 6 + lc as usize => short(cmd[cmd.len() - 1]),
+        Some(&[0, hi, lo, ..]) if cmd.len() == 9 + u16::from_be_bytes([hi, lo]) as usize => extended(&cmd[cmd.len() - 2..]),
+        _ => 0,
+    }
+}
+
+/// A short case-4 command (header, Lc, data, Le) without its Le, as T=0 requires;
+/// any other command is returned unchanged
+fn without_case4_le(cmd: &[u8]) -> &[u8] {
+    match cmd.get(4) {
+        Some(&lc) if lc != 0 && cmd.len() == 6 + lc as usize => &cmd[..cmd.len() - 1],
+        _ => cmd,
+    }
+}
+
+/// The command with its Le set to `le` (replacing an existing short Le, or appending one)
+fn with_le(cmd: &[u8], le: u8) -> Vec<u8> {
+    let has_le = match cmd.len() {
+        0..=4 => false,
+        5 => true,
+        len => len == 6 + cmd[4] as usize,
+    };
+    let mut command = cmd.to_vec();
+    if has_le {
+        command.pop();
+    }
+    command.push(le);
+    command
+}
+
+/// Transmit an APDU through `transmit`, following 61xx with GET RESPONSE (CLA and P2 from
+/// `get_response`) and re-issuing once with the corrected Le on 6Cxx
+///
+/// The returned status word is the one from the last exchange, so a completed
+/// GET RESPONSE chain reports 90 00 rather than the first 61xx.
+fn transmit_apdu(transmit: impl Fn(&[u8]) -> pcsc::Result<Vec<u8>>, cmd: &[u8], max_get_response: u32, get_response: [u8; 2]) -> Result<TransmitResult> {
+    let (mut data, mut sw1, mut sw2) = transmit_once(&transmit, cmd)?;
+    if sw1 == 0x6C {
+        (data, sw1, sw2) = transmit_once(&transmit, &with_le(cmd, sw2))?;
+    }
+    
+    // SW1/SW2 end up as the status of the last exchange; 61 00 means 256 more bytes
+    let mut get_response_count = 0;
+    while sw1 == 0x61 && get_response_count < max_get_response {
+        let get_response_cmd = [get_response[0], 0xC0, 0x00, get_response[1], sw2];
+        let Ok((chunk, next_sw1, next_sw2)) = transmit_once(&transmit, &get_response_cmd) else {
+            break;
+        };
+        get_response_count += 1;
+        data.extend_from_slice(&chunk);
+        (sw1, sw2) = (next_sw1, next_sw2);
+    }
+    
+    Ok(TransmitResult {
+        data: Buffer::from(data),
+        sw1,
+        sw2,
+        status_word: u16::from_be_bytes([sw1, sw2]),
+        is_success: sw1 == 0x90 && sw2 == 0x00,
+        get_response_iterations: if get_response_count > 0 { Some(get_response_count) } else { None },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    /// A transmitter that answers with `responses` in order, then fails with `exhausted`,
+    /// and records the commands it got
+    struct Script {
+        responses: RefCell<VecDeque<Vec<u8>>>,
+        sent: RefCell<Vec<Vec<u8>>>,
+        exhausted: pcsc::Error,
+    }
+
+    impl Script {
+        fn new(responses: &[&[u8]]) -> Self {
+            Self {
+                responses: RefCell::new(responses.iter().map(|response| response.to_vec()).collect()),
+                sent: RefCell::default(),
+                exhausted: pcsc::Error::UnknownError,
+            }
+        }
+
+        fn then_fail(self, exhausted: pcsc::Error) -> Self {
+            Self { exhausted, ..self }
+        }
+
+        fn transmitter(&self) -> impl Fn(&[u8]) -> pcsc::Result<Vec<u8>> + '_ {
+            |cmd| {
+                self.sent.borrow_mut().push(cmd.to_vec());
+                self.responses.borrow_mut().pop_front().ok_or(self.exhausted)
+            }
+        }
+
+        fn sent(&self) -> Vec<Vec<u8>> {
+            self.sent.borrow().clone()
+        }
+    }
+
+    const READ_BINARY: [u8; 5] = [0x00, 0xB0, 0x00, 0x00, 0x04];
+
+    #[test]
+    fn empty_response_is_invalid() {
+        let script = Script::new(&[&[]]);
+        let Err(e) = transmit_apdu(script.transmitter(), &READ_BINARY, 3, [0x00, 0x00]) else {
+            panic!("expected INVALID_RESPONSE");
+        };
+        assert!(has_reason(&e, INVALID_RESPONSE));
+    }
+
+    #[test]
+    fn one_byte_response_is_invalid() {
+        let script = Script::new(&[&[0x90]]);
+        let Err(e) = transmit_apdu(script.transmitter(), &READ_BINARY, 3, [0x00, 0x00]) else {
+            panic!("expected INVALID_RESPONSE");
+        };
+        assert!(has_reason(&e, INVALID_RESPONSE));
+    }
+
+    #[test]
+    fn two_byte_response_is_status_only() {
+        let script = Script::new(&[&[0x6A, 0x82]]);
+        let result = transmit_apdu(script.transmitter(), &READ_BINARY, 3, [0x00, 0x00]).unwrap();
+        assert!(result.data.is_empty());
+        assert_eq!((result.sw1, result.sw2, result.status_word), (0x6A, 0x82, 0x6A82));
+        assert!(!result.is_success);
+        assert_eq!(result.get_response_iterations, None);
+    }
+
+    #[test]
+    fn wrong_le_is_reissued_with_the_corrected_le() {
+        let script = Script::new(&[&[0x6C, 0x02], &[0x01, 0x02, 0x90, 0x00]]);
+        let result = transmit_apdu(script.transmitter(), &READ_BINARY, 3, [0x00, 0x00]).unwrap();
+        assert_eq!(&result.data[..], &[0x01, 0x02]);
+        assert!(result.is_success);
+        assert_eq!(script.sent(), vec![READ_BINARY.to_vec(), vec![0x00, 0xB0, 0x00, 0x00, 0x02]]);
+    }
+
+    #[test]
+    fn wrong_le_is_reissued_only_once() {
+        let script = Script::new(&[&[0x6C, 0x02], &[0x6C, 0x01]]);
+        let result = transmit_apdu(script.transmitter(), &READ_BINARY, 3, [0x00, 0x00]).unwrap();
+        assert_eq!(result.status_word, 0x6C01);
+        assert_eq!(script.sent().len(), 2);
+    }
+
+    #[test]
+    fn get_response_chain_is_followed_to_the_final_status() {
+        let script = Script::new(&[&[0x61, 0x02], &[0x01, 0x02, 0x61, 0x01], &[0x03, 0x90, 0x00]]);
+        let result = transmit_apdu(script.transmitter(), &READ_BINARY, 3, [0x00, 0x00]).unwrap();
+        assert_eq!(&result.data[..], &[0x01, 0x02, 0x03]);
+        assert_eq!(result.status_word, 0x9000);
+        assert_eq!(result.get_response_iterations, Some(2));
+        assert_eq!(script.sent()[1..], [vec![0x00, 0xC0, 0x00, 0x00, 0x02], vec![0x00, 0xC0, 0x00, 0x00, 0x01]]);
+    }
+
+    #[test]
+    fn get_response_uses_the_given_cla_and_p2() {
+        let script = Script::new(&[&[0x61, 0x01], &[0x01, 0x90, 0x00]]);
+        transmit_apdu(script.transmitter(), &READ_BINARY, 3, [0x80, 0x01]).unwrap();
+        assert_eq!(script.sent()[1], [0x80, 0xC0, 0x00, 0x01, 0x01]);
+    }
+
+    #[test]
+    fn get_response_chain_stops_at_the_limit() {
+        let script = Script::new(&[&[0x61, 0x01], &[0x01, 0x61, 0x01], &[0x02, 0x90, 0x00]]);
+        let result = transmit_apdu(script.transmitter(), &READ_BINARY, 1, [0x00, 0x00]).unwrap();
+        assert_eq!(&result.data[..], &[0x01]);
+        assert_eq!(result.status_word, 0x6101);
+        assert_eq!(result.get_response_iterations, Some(1));
+    }
+
+    #[test]
+    fn call_after_a_timeout_fails_fast_until_the_worker_returns() {
+        let card = Card::dry_run();
+        let hung = card.with_worker_timeout(|card| {
+            let _connection = card.lock()?;
+            std::thread::sleep(Duration::from_millis(300));
+            Ok(())
+        }, Duration::from_millis(20));
+        assert!(has_reason(&hung.unwrap_err(), TIMEOUT));
+        
+        let started = Instant::now();
+        let Err(e) = card.lock() else { panic!("lock should fail while the timed-out worker holds it") };
+        assert!(has_reason(&e, TIMEOUT));
+        assert!(started.elapsed() < Duration::from_millis(100));
+        
+        std::thread::sleep(Duration::from_millis(500));
+        assert_eq!(card.stalled.load(Ordering::SeqCst), 0);
+        assert!(card.lock().is_ok());
+    }
+
+    /// SELECT by AID with Le: a case-4 command
+    const SELECT_CASE4: [u8; 8] = [0x00, 0xA4, 0x04, 0x00, 0x02, 0xA0, 0x00, 0x00];
+
+    fn card_with_protocol(protocol: pcsc::Protocol) -> Card {
+        let mut card = Card::dry_run();
+        card.protocol = Some(protocol);
+        card
+    }
+
+    #[test]
+    fn t0_sends_case4_without_le_and_collects_the_data_with_get_response() {
+        let card = card_with_protocol(pcsc::Protocol::T0);
+        let script = Script::new(&[&[0x61, 0x02], &[0x6F, 0x00, 0x90, 0x00]]);
+        let result = card.transmit_for_protocol(script.transmitter(), &SELECT_CASE4, 3, 0x00).unwrap();
+        assert_eq!(&result.data[..], &[0x6F, 0x00]);
+        assert_eq!(script.sent(), vec![SELECT_CASE4[..7].to_vec(), vec![0x00, 0xC0, 0x00, 0x00, 0x02]]);
+    }
+
+    #[test]
+    fn t0_leaves_other_cases_alone() {
+        let card = card_with_protocol(pcsc::Protocol::T0);
+        let script = Script::new(&[&[0x90, 0x00], &[0x01, 0x02, 0x03, 0x04, 0x90, 0x00]]);
+        card.transmit_for_protocol(script.transmitter(), &SELECT_CASE4[..7], 3, 0x00).unwrap();
+        card.transmit_for_protocol(script.transmitter(), &READ_BINARY, 3, 0x00).unwrap();
+        assert_eq!(script.sent(), vec![SELECT_CASE4[..7].to_vec(), READ_BINARY.to_vec()]);
+    }
+
+    #[test]
+    fn t1_sends_case4_unchanged() {
+        let card = card_with_protocol(pcsc::Protocol::T1);
+        let script = Script::new(&[&[0x6F, 0x00, 0x90, 0x00]]);
+        let result = card.transmit_for_protocol(script.transmitter(), &SELECT_CASE4, 3, 0x00).unwrap();
+        assert_eq!(&result.data[..], &[0x6F, 0x00]);
+        assert_eq!(script.sent(), vec![SELECT_CASE4.to_vec()]);
+    }
+
+    #[test]
+    fn transmit_after_disconnect_fails_with_not_connected() {
+        let mut card = Card::dry_run();
+        card.dry_run = false;
+        card.disconnect(0).unwrap();
+        for timeout_ms in [None, Some(1000)] {
+            let Err(e) = card.transmit(Buffer::from(READ_BINARY.to_vec()), 4, None, timeout_ms) else {
+                panic!("transmit after disconnect should fail");
+            };
+            assert!(has_reason(&e, NOT_CONNECTED));
+            assert!(e.reason.contains("not connected"));
+        }
+    }
+
+    #[test]
+    fn retry_resends_once_after_a_retryable_status() {
+        let script = Script::new(&[&[0x69, 0x82], &[0x90, 0x00]]);
+        let result = retry_transmit(
+            || transmit_apdu(script.transmitter(), &READ_BINARY, 3, [0x00, 0x00]),
+            3,
+            Duration::ZERO,
+            None,
+        ).unwrap();
+        assert_eq!(result.status_word, 0x9000);
+        assert_eq!(script.sent(), vec![READ_BINARY.to_vec(), READ_BINARY.to_vec()]);
+    }
+
+    #[test]
+    fn retry_stops_at_once_when_the_card_is_removed() {
+        let script = Script::new(&[&[0x6F, 0x00]]).then_fail(pcsc::Error::RemovedCard);
+        let Err(e) = retry_transmit(
+            || transmit_apdu(script.transmitter(), &READ_BINARY, 3, [0x00, 0x00]),
+            3,
+            Duration::ZERO,
+            None,
+        ) else {
+            panic!("expected CARD_REMOVED");
+        };
+        assert!(has_reason(&e, CARD_REMOVED));
+        assert_eq!(script.sent().len(), 2);
+    }
+
+    #[test]
+    fn too_small_buffer_is_doubled_and_the_command_resent() {
+        let buffers = RefCell::new(Vec::new());
+        let response = transmit_growing(|cmd, buffer| {
+            assert_eq!(cmd, READ_BINARY);
+            buffers.borrow_mut().push(buffer.len());
+            if buffers.borrow().len() == 1 {
+                return Err(pcsc::Error::InsufficientBuffer);
+            }
+            buffer[..3].copy_from_slice(&[0x01, 0x90, 0x00]);
+            Ok(3)
+        }, &READ_BINARY, 4).unwrap();
+        assert_eq!(response, [0x01, 0x90, 0x00]);
+        assert_eq!(*buffers.borrow(), [6, 12]);
+    }
+}
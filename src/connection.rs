@@ -0,0 +1,293 @@
+// Card handle shared by a Card and its worker clones, with the transaction begin_transaction opened
+
+use crate::error::{error, pcsc_error, DISCONNECT_FAILED, NOT_CONNECTED, NOT_TRANSACTED, TRANSACTION_FAILED};
+use crate::logger;
+use napi::Result;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::ptr::NonNull;
+
+/// The card operations Connection relies on: pcsc::Card, or a recording mock in tests
+pub(crate) trait Handle: Sized + 'static {
+    /// Ends the transaction (LeaveCard) when dropped without `end`
+    type Transaction<'tx>: Deref<Target = Self>;
+    
+    fn begin(&mut self) -> pcsc::Result<Self::Transaction<'_>>;
+    fn end(transaction: Self::Transaction<'_>, disposition: pcsc::Disposition) -> pcsc::Result<()>;
+    fn disconnect(self, disposition: pcsc::Disposition) -> std::result::Result<(), (Self, pcsc::Error)>;
+}
+
+impl Handle for pcsc::Card {
+    type Transaction<'tx> = pcsc::Transaction<'tx>;
+    
+    fn begin(&mut self) -> pcsc::Result<pcsc::Transaction<'_>> {
+        self.transaction()
+    }
+    
+    fn end(transaction: pcsc::Transaction<'_>, disposition: pcsc::Disposition) -> pcsc::Result<()> {
+        // A transaction that failed to end is dropped here, which tries once more with LeaveCard
+        transaction.end(disposition).map_err(|(_, e)| e)
+    }
+    
+    fn disconnect(self, disposition: pcsc::Disposition) -> std::result::Result<(), (Self, pcsc::Error)> {
+        pcsc::Card::disconnect(self, disposition)
+    }
+}
+
+/// The connected card handle, if any
+///
+/// pcsc only ends a transaction through the `Transaction` that began it, which borrows the
+/// card. To keep one open between begin_transaction and end_transaction, the card moves to
+/// its own allocation and the transaction holds the only reference to it until it ends.
+///
+/// Invariants for the Transacted state, relied on by every `unsafe` block below:
+/// - `card` came from Box::leak and is freed (Box::from_raw) exactly once, in end_transaction,
+///   after the transaction has been consumed, so the `'static` borrow never outlives the card;
+/// - nothing but `transaction` reaches the card while it is open;
+/// - the state is set to Released before the transaction is taken out, so a panic while ending
+///   it leaks the card instead of freeing it twice or leaving a dangling Transacted state.
+pub(crate) struct Connection<C: Handle = pcsc::Card> {
+    state: State<C>,
+}
+
+enum State<C: Handle> {
+    Idle(C),
+    Transacted {
+        /// Owning pointer (from Box::leak), reached only through `transaction` until it ends
+        card: NonNull<C>,
+        transaction: ManuallyDrop<C::Transaction<'static>>,
+    },
+    Released,
+}
+
+// SAFETY: the only non-Send part is the NonNull, which is the card's sole owner and is only
+// dereferenced through `transaction` (a borrow of that same card) or when it is freed
+unsafe impl<C: Handle + Send> Send for Connection<C> where for<'tx> C::Transaction<'tx>: Send {}
+
+impl<C: Handle> Connection<C> {
+    pub(crate) fn new(card: Option<C>) -> Self {
+        Self { state: card.map_or(State::Released, State::Idle) }
+    }
+
+    /// The card handle, unless it has been disconnected
+    pub(crate) fn handle(&self) -> Option<&C> {
+        match &self.state {
+            State::Idle(card) => Some(card),
+            State::Transacted { transaction, .. } => Some(&***transaction),
+            State::Released => None,
+        }
+    }
+
+    /// The card handle for reconnecting or a scoped transaction; an open transaction is
+    /// ended first (LeaveCard), since it borrows the handle
+    pub(crate) fn handle_mut(&mut self) -> Option<&mut C> {
+        self.abandon_transaction();
+        match &mut self.state {
+            State::Idle(card) => Some(card),
+            _ => None,
+        }
+    }
+
+    /// Begin a transaction that stays open until end_transaction
+    pub(crate) fn begin_transaction(&mut self) -> Result<()> {
+        let card = match std::mem::replace(&mut self.state, State::Released) {
+            State::Idle(card) => NonNull::from(Box::leak(Box::new(card))),
+            State::Transacted { card, transaction } => {
+                self.state = State::Transacted { card, transaction };
+                return Err(error(TRANSACTION_FAILED, "A transaction is already in progress"));
+            }
+            State::Released => return Err(error(NOT_CONNECTED, "Card is not connected")),
+        };
+        
+        // SAFETY: the pointer is valid until end_transaction frees it, which happens only after
+        // the transaction holding this borrow has been consumed
+        match unsafe { &mut *card.as_ptr() }.begin() {
+            Ok(transaction) => {
+                self.state = State::Transacted { card, transaction: ManuallyDrop::new(transaction) };
+                Ok(())
+            }
+            Err(e) => {
+                // SAFETY: the failed begin left no reference to the card, and it is freed only here
+                self.state = State::Idle(*unsafe { Box::from_raw(card.as_ptr()) });
+                Err(pcsc_error(TRANSACTION_FAILED, "Failed to begin transaction", e))
+            }
+        }
+    }
+
+    /// End the transaction opened by begin_transaction with `disposition`
+    ///
+    /// The handle stays usable either way; if SCardEndTransaction fails, the transaction is
+    /// dropped, which tries once more with LeaveCard.
+    pub(crate) fn end_transaction(&mut self, disposition: pcsc::Disposition) -> Result<()> {
+        let (card, transaction) = match std::mem::replace(&mut self.state, State::Released) {
+            State::Transacted { card, transaction } => (card, transaction),
+            other => {
+                self.state = other;
+                return Err(error(NOT_TRANSACTED, "No transaction in progress"));
+            }
+        };
+        
+        let ended = C::end(ManuallyDrop::into_inner(transaction), disposition);
+        // SAFETY: the transaction, the only reference to the card, was consumed (and dropped) by
+        // C::end; if that panicked, the state is already Released and the card leaks instead
+        self.state = State::Idle(*unsafe { Box::from_raw(card.as_ptr()) });
+        match ended {
+            Ok(()) => Ok(()),
+            Err(pcsc::Error::NotTransacted) => {
+                Err(error(NOT_TRANSACTED, "Transaction was lost before it ended (card reset?)"))
+            }
+            Err(e) => Err(pcsc_error(TRANSACTION_FAILED, "Failed to end transaction", e)),
+        }
+    }
+
+    /// Disconnect and drop the handle; later calls fail with NOT_CONNECTED
+    pub(crate) fn release(&mut self, disposition: pcsc::Disposition) -> Result<()> {
+        self.abandon_transaction();
+        if let State::Idle(card) = std::mem::replace(&mut self.state, State::Released) {
+            if let Err((card, e)) = card.disconnect(disposition) {
+                self.state = State::Idle(card);
+                return Err(pcsc_error(DISCONNECT_FAILED, "Failed to disconnect card", e));
+            }
+        }
+        Ok(())
+    }
+
+    /// End an open transaction with LeaveCard, logging rather than failing if that goes wrong
+    fn abandon_transaction(&mut self) {
+        if !matches!(self.state, State::Transacted { .. }) {
+            return;
+        }
+        if let Err(e) = self.end_transaction(pcsc::Disposition::LeaveCard) {
+            logger::log(format!("Failed to end open transaction: {}", e.reason));
+        }
+    }
+}
+
+impl<C: Handle> Drop for Connection<C> {
+    fn drop(&mut self) {
+        self.abandon_transaction();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Log = Rc<RefCell<Vec<&'static str>>>;
+
+    /// Records the calls Connection makes, including when the card itself is freed
+    struct MockCard {
+        log: Log,
+        fail_end: bool,
+    }
+
+    struct MockTransaction<'tx> {
+        card: &'tx mut MockCard,
+        ended: bool,
+    }
+
+    impl Deref for MockTransaction<'_> {
+        type Target = MockCard;
+        
+        fn deref(&self) -> &MockCard {
+            self.card
+        }
+    }
+
+    impl Drop for MockTransaction<'_> {
+        fn drop(&mut self) {
+            if !self.ended {
+                self.card.log.borrow_mut().push("leave");
+            }
+        }
+    }
+
+    impl Drop for MockCard {
+        fn drop(&mut self) {
+            self.log.borrow_mut().push("free");
+        }
+    }
+
+    impl Handle for MockCard {
+        type Transaction<'tx> = MockTransaction<'tx>;
+        
+        fn begin(&mut self) -> pcsc::Result<MockTransaction<'_>> {
+            self.log.borrow_mut().push("begin");
+            Ok(MockTransaction { card: self, ended: false })
+        }
+        
+        fn end(mut transaction: MockTransaction<'_>, _disposition: pcsc::Disposition) -> pcsc::Result<()> {
+            transaction.card.log.borrow_mut().push("end");
+            if transaction.card.fail_end {
+                return Err(pcsc::Error::UnknownError);
+            }
+            transaction.ended = true;
+            Ok(())
+        }
+        
+        fn disconnect(self, _disposition: pcsc::Disposition) -> std::result::Result<(), (Self, pcsc::Error)> {
+            self.log.borrow_mut().push("disconnect");
+            Ok(())
+        }
+    }
+
+    fn connection(fail_end: bool) -> (Connection<MockCard>, Log) {
+        let log = Log::default();
+        (Connection::new(Some(MockCard { log: log.clone(), fail_end })), log)
+    }
+
+    #[test]
+    fn drop_while_transacted_ends_the_transaction_before_freeing_the_card() {
+        let (mut conn, log) = connection(false);
+        conn.begin_transaction().unwrap();
+        drop(conn);
+        assert_eq!(*log.borrow(), ["begin", "end", "free"]);
+    }
+
+    #[test]
+    fn reconnect_while_transacted_ends_the_transaction_first() {
+        let (mut conn, log) = connection(false);
+        conn.begin_transaction().unwrap();
+        assert!(conn.handle_mut().is_some());
+        assert_eq!(*log.borrow(), ["begin", "end"]);
+        
+        // The handle is Idle again, so a new transaction can begin
+        conn.begin_transaction().unwrap();
+        conn.end_transaction(pcsc::Disposition::LeaveCard).unwrap();
+        drop(conn);
+        assert_eq!(*log.borrow(), ["begin", "end", "begin", "end", "free"]);
+    }
+
+    #[test]
+    fn failed_end_leaves_the_card_before_freeing_it() {
+        let (mut conn, log) = connection(true);
+        conn.begin_transaction().unwrap();
+        assert!(conn.end_transaction(pcsc::Disposition::ResetCard).is_err());
+        assert!(conn.handle().is_some());
+        drop(conn);
+        assert_eq!(*log.borrow(), ["begin", "end", "leave", "free"]);
+    }
+
+    #[test]
+    fn panic_while_transacted_still_ends_the_transaction() {
+        let (conn, log) = connection(false);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+            let mut conn = conn;
+            conn.begin_transaction().unwrap();
+            panic!("caller panicked mid-transaction");
+        }));
+        assert!(result.is_err());
+        assert_eq!(*log.borrow(), ["begin", "end", "free"]);
+    }
+
+    #[test]
+    fn release_while_transacted_ends_the_transaction_then_disconnects() {
+        let (mut conn, log) = connection(false);
+        conn.begin_transaction().unwrap();
+        conn.release(pcsc::Disposition::LeaveCard).unwrap();
+        assert!(conn.handle().is_none());
+        assert_eq!(*log.borrow(), ["begin", "end", "disconnect", "free"]);
+    }
+}
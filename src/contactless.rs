@@ -0,0 +1,96 @@
+// Contactless helpers built on the PC/SC part 3 pseudo-APDUs (class FF) handled by the reader
+
+use crate::card::Card;
+use crate::error::{error, AUTHENTICATION_FAILED, COMMAND_FAILED, FUNCTION_UNSUPPORTED, INVALID_ARGUMENT};
+use crate::types::TransmitResult;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// GET DATA for the card's UID (ISO 14443 UID / FeliCa IDm)
+const GET_UID: [u8; 5] = [0xFF, 0xCA, 0x00, 0x00, 0x00];
+
+/// MIFARE Classic key and block sizes
+const MIFARE_KEY_LENGTH: usize = 6;
+const MIFARE_BLOCK_LENGTH: usize = 16;
+
+/// Key type bytes of GENERAL AUTHENTICATE
+const MIFARE_KEY_A: u8 = 0x60;
+const MIFARE_KEY_B: u8 = 0x61;
+
+#[napi]
+impl Card {
+    /// Read the contactless card's UID with the reader pseudo-APDU FF CA 00 00 00
+    ///
+    /// Fails with FUNCTION_UNSUPPORTED when the reader doesn't implement it (6A81).
+    #[napi(catch_unwind)]
+    pub fn get_uid(&self) -> Result<Buffer> {
+        let card = self.lock()?;
+        let result = self.exchange(card.handle(), &GET_UID, 256, 3)?;
+        reader_result(result, "GET DATA (UID)")
+    }
+
+    /// Load a 6-byte MIFARE key into the reader's volatile key slot (FF 82 00 <slot> 06 <key>)
+    #[napi(catch_unwind)]
+    pub fn mifare_load_key(&self, key: Buffer, key_slot: u8) -> Result<()> {
+        if key.len() != MIFARE_KEY_LENGTH {
+            return Err(error(INVALID_ARGUMENT, format!("MIFARE key must be {} bytes, got {}", MIFARE_KEY_LENGTH, key.len())));
+        }
+        
+        let mut command = vec![0xFF, 0x82, 0x00, key_slot, MIFARE_KEY_LENGTH as u8];
+        command.extend_from_slice(&key);
+        let card = self.lock()?;
+        let result = self.exchange_redacted(card.handle(), &command, 2, 0, 5)?;
+        reader_result(result, "LOAD KEY").map(|_| ())
+    }
+
+    /// Authenticate a block with the key in `key_slot` (FF 86 GENERAL AUTHENTICATE)
+    ///
+    /// `key_type` is 0x60 for key A or 0x61 for key B. A rejected key (63 00) fails with
+    /// AUTHENTICATION_FAILED.
+    #[napi(catch_unwind)]
+    pub fn mifare_authenticate(&self, block: u8, key_type: u8, key_slot: u8) -> Result<()> {
+        if key_type != MIFARE_KEY_A && key_type != MIFARE_KEY_B {
+            return Err(error(INVALID_ARGUMENT, format!("Key type must be 0x60 (A) or 0x61 (B), got 0x{:02X}", key_type)));
+        }
+        
+        let command = [0xFF, 0x86, 0x00, 0x00, 0x05, 0x01, 0x00, block, key_type, key_slot];
+        let card = self.lock()?;
+        let result = self.exchange(card.handle(), &command, 2, 0)?;
+        if result.sw1 == 0x63 && result.sw2 == 0x00 {
+            return Err(error(AUTHENTICATION_FAILED, format!("Authentication of block {} was rejected", block)));
+        }
+        reader_result(result, "GENERAL AUTHENTICATE").map(|_| ())
+    }
+
+    /// Read a 16-byte MIFARE Classic block (FF B0 00 <block> 10); the block must be authenticated first
+    #[napi(catch_unwind)]
+    pub fn mifare_read_block(&self, block: u8) -> Result<Buffer> {
+        let command = [0xFF, 0xB0, 0x00, block, MIFARE_BLOCK_LENGTH as u8];
+        let card = self.lock()?;
+        let result = self.exchange(card.handle(), &command, MIFARE_BLOCK_LENGTH as u32, 3)?;
+        reader_result(result, "READ BINARY")
+    }
+
+    /// Write a 16-byte MIFARE Classic block (FF D6 00 <block> 10 <data>); the block must be authenticated first
+    #[napi(catch_unwind)]
+    pub fn mifare_write_block(&self, block: u8, data: Buffer) -> Result<()> {
+        if data.len() != MIFARE_BLOCK_LENGTH {
+            return Err(error(INVALID_ARGUMENT, format!("MIFARE block must be {} bytes, got {}", MIFARE_BLOCK_LENGTH, data.len())));
+        }
+        
+        let mut command = vec![0xFF, 0xD6, 0x00, block, MIFARE_BLOCK_LENGTH as u8];
+        command.extend_from_slice(&data);
+        let card = self.lock()?;
+        let result = self.exchange(card.handle(), &command, 2, 0)?;
+        reader_result(result, "UPDATE BINARY").map(|_| ())
+    }
+}
+
+/// Response data of a pseudo-APDU, or FUNCTION_UNSUPPORTED (6A81) / COMMAND_FAILED
+fn reader_result(result: TransmitResult, command_name: &str) -> Result<Buffer> {
+    match (result.sw1, result.sw2) {
+        (0x90, 0x00) => Ok(result.data),
+        (0x6A, 0x81) => Err(error(FUNCTION_UNSUPPORTED, format!("Reader does not support {}", command_name))),
+        (sw1, sw2) => Err(error(COMMAND_FAILED, format!("{} failed with status {:02X}{:02X}", command_name, sw1, sw2))),
+    }
+}
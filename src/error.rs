@@ -0,0 +1,159 @@
+use napi::Status;
+
+// Machine-readable error reasons
+pub(crate) const CONTEXT_FAILED: &str = "CONTEXT_FAILED";
+pub(crate) const LOCK_FAILED: &str = "LOCK_FAILED";
+pub(crate) const LIST_READERS_FAILED: &str = "LIST_READERS_FAILED";
+pub(crate) const READER_NOT_FOUND: &str = "READER_NOT_FOUND";
+pub(crate) const INVALID_READER_NAME: &str = "INVALID_READER_NAME";
+pub(crate) const STATUS_FAILED: &str = "STATUS_FAILED";
+pub(crate) const CONNECT_FAILED: &str = "CONNECT_FAILED";
+pub(crate) const NO_CARD_PRESENT: &str = "NO_CARD_PRESENT";
+pub(crate) const SHARING_VIOLATION: &str = "SHARING_VIOLATION";
+pub(crate) const READER_UNAVAILABLE: &str = "READER_UNAVAILABLE";
+pub(crate) const UNRESPONSIVE_CARD: &str = "UNRESPONSIVE_CARD";
+pub(crate) const CARD_REMOVED: &str = "CARD_REMOVED";
+pub(crate) const CARD_RESET: &str = "CARD_RESET";
+pub(crate) const NO_READERS_AVAILABLE: &str = "NO_READERS_AVAILABLE";
+pub(crate) const NO_SERVICE: &str = "NO_SERVICE";
+pub(crate) const INVALID_HANDLE: &str = "INVALID_HANDLE";
+pub(crate) const CANCELLED: &str = "CANCELLED";
+pub(crate) const TRANSMIT_FAILED: &str = "TRANSMIT_FAILED";
+pub(crate) const INVALID_RESPONSE: &str = "INVALID_RESPONSE";
+pub(crate) const COMMAND_FAILED: &str = "COMMAND_FAILED";
+pub(crate) const CONTROL_FAILED: &str = "CONTROL_FAILED";
+pub(crate) const ATTRIBUTE_FAILED: &str = "ATTRIBUTE_FAILED";
+pub(crate) const ATTRIBUTE_UNSUPPORTED: &str = "ATTRIBUTE_UNSUPPORTED";
+pub(crate) const NOT_CONNECTED: &str = "NOT_CONNECTED";
+pub(crate) const RECONNECT_FAILED: &str = "RECONNECT_FAILED";
+pub(crate) const DISCONNECT_FAILED: &str = "DISCONNECT_FAILED";
+pub(crate) const TRANSACTION_FAILED: &str = "TRANSACTION_FAILED";
+pub(crate) const NOT_TRANSACTED: &str = "NOT_TRANSACTED";
+pub(crate) const NOT_THAI_ID: &str = "NOT_THAI_ID";
+pub(crate) const FILE_NOT_FOUND: &str = "FILE_NOT_FOUND";
+pub(crate) const NDEF_NOT_FOUND: &str = "NDEF_NOT_FOUND";
+pub(crate) const SELECT_FAILED: &str = "SELECT_FAILED";
+pub(crate) const WRONG_PIN: &str = "WRONG_PIN";
+pub(crate) const PIN_BLOCKED: &str = "PIN_BLOCKED";
+pub(crate) const FUNCTION_UNSUPPORTED: &str = "FUNCTION_UNSUPPORTED";
+pub(crate) const AUTHENTICATION_FAILED: &str = "AUTHENTICATION_FAILED";
+pub(crate) const INVALID_ARGUMENT: &str = "INVALID_ARGUMENT";
+pub(crate) const INVALID_APDU: &str = "INVALID_APDU";
+pub(crate) const TIMEOUT: &str = "TIMEOUT";
+pub(crate) const TASK_FAILED: &str = "TASK_FAILED";
+
+/// Build a napi error tagged with a machine-readable reason
+///
+/// The message is prefixed with `[REASON]`; the TypeScript wrapper moves it into
+/// `err.code`/`err.reason` and runs the message through the error translator.
+pub(crate) fn error(reason: &str, message: impl std::fmt::Display) -> napi::Error {
+    napi::Error::new(Status::GenericFailure, format!("[{}] {}", reason, message))
+}
+
+/// Whether an error built by `error` carries the given reason
+pub(crate) fn has_reason(e: &napi::Error, reason: &str) -> bool {
+    e.reason.strip_prefix('[')
+        .and_then(|rest| rest.strip_prefix(reason))
+        .is_some_and(|rest| rest.starts_with(']'))
+}
+
+/// Common PC/SC failures that get their own reason wherever they occur
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SmartCardError {
+    NoCardPresent,
+    CardRemoved,
+    CardReset,
+    SharingViolation,
+    ReaderUnavailable,
+    UnresponsiveCard,
+    NoReadersAvailable,
+    NoService,
+    InvalidHandle,
+    Timeout,
+    Cancelled,
+}
+
+impl SmartCardError {
+    pub(crate) fn from_pcsc(e: pcsc::Error) -> Option<Self> {
+        match e {
+            pcsc::Error::NoSmartcard => Some(Self::NoCardPresent),
+            pcsc::Error::RemovedCard => Some(Self::CardRemoved),
+            pcsc::Error::ResetCard => Some(Self::CardReset),
+            pcsc::Error::SharingViolation => Some(Self::SharingViolation),
+            pcsc::Error::ReaderUnavailable => Some(Self::ReaderUnavailable),
+            pcsc::Error::UnresponsiveCard => Some(Self::UnresponsiveCard),
+            pcsc::Error::NoReadersAvailable => Some(Self::NoReadersAvailable),
+            pcsc::Error::NoService | pcsc::Error::ServiceStopped => Some(Self::NoService),
+            pcsc::Error::InvalidHandle => Some(Self::InvalidHandle),
+            pcsc::Error::Timeout => Some(Self::Timeout),
+            pcsc::Error::Cancelled => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn code(self) -> &'static str {
+        match self {
+            Self::NoCardPresent => NO_CARD_PRESENT,
+            Self::CardRemoved => CARD_REMOVED,
+            Self::CardReset => CARD_RESET,
+            Self::SharingViolation => SHARING_VIOLATION,
+            Self::ReaderUnavailable => READER_UNAVAILABLE,
+            Self::UnresponsiveCard => UNRESPONSIVE_CARD,
+            Self::NoReadersAvailable => NO_READERS_AVAILABLE,
+            Self::NoService => NO_SERVICE,
+            Self::InvalidHandle => INVALID_HANDLE,
+            Self::Timeout => TIMEOUT,
+            Self::Cancelled => CANCELLED,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::NoCardPresent => "No card present in reader",
+            Self::CardRemoved => "Card was removed",
+            Self::CardReset => "Card was reset by another application",
+            Self::SharingViolation => "Card is in use by another application",
+            Self::ReaderUnavailable => "Reader is unavailable",
+            Self::UnresponsiveCard => "Card is not responding (mute)",
+            Self::NoReadersAvailable => "No readers available",
+            Self::NoService => "PC/SC service is not running",
+            Self::InvalidHandle => "PC/SC handle is no longer valid (service restarted?)",
+            Self::Timeout => "Operation timed out",
+            Self::Cancelled => "Operation was cancelled",
+        }
+    }
+}
+
+/// Build an error for a failed PC/SC call; known failures get their own reason,
+/// anything else falls back to `reason`
+pub(crate) fn pcsc_error(reason: &str, message: &str, e: pcsc::Error) -> napi::Error {
+    match SmartCardError::from_pcsc(e) {
+        Some(kind) => error(kind.code(), format!("{}: {}", message, kind.description())),
+        None => error(reason, format!("{}: {}", message, e)),
+    }
+}
+
+/// Map a connect failure
+///
+/// SCardConnect reports an empty reader as RemovedCard on some platforms; for a connect that
+/// means no card is present, not that one was pulled mid-session.
+pub(crate) fn connect_error(e: pcsc::Error) -> napi::Error {
+    let e = match e {
+        pcsc::Error::RemovedCard => pcsc::Error::NoSmartcard,
+        e => e,
+    };
+    pcsc_error(CONNECT_FAILED, "Failed to connect to card", e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connect_error_reports_a_removed_card_as_no_card_present() {
+        assert!(has_reason(&connect_error(pcsc::Error::RemovedCard), NO_CARD_PRESENT));
+        assert!(has_reason(&connect_error(pcsc::Error::NoSmartcard), NO_CARD_PRESENT));
+        // Outside connect a removal keeps its own reason
+        assert!(has_reason(&pcsc_error(TRANSMIT_FAILED, "Failed to transmit", pcsc::Error::RemovedCard), CARD_REMOVED));
+    }
+}
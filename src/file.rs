@@ -0,0 +1,172 @@
+// ISO 7816-4 file helpers (SELECT by file ID, READ BINARY)
+
+use crate::card::{Card, SELECT_RESPONSE_LENGTH};
+use crate::error::{error, COMMAND_FAILED, FILE_NOT_FOUND, INVALID_ARGUMENT};
+use crate::types::{FileContents, TransmitResult};
+use crate::utils::{fcp_file_size, hex, read_tlv};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// Largest offset addressable by P1/P2 in READ BINARY
+const MAX_BINARY_OFFSET: u32 = 0x7FFF;
+
+impl Card {
+    /// SELECT an EF by file ID, requesting its FCP; returns the FCP bytes
+    pub(crate) fn select_file(&self, card: Option<&pcsc::Card>, file_id: &[u8]) -> Result<Vec<u8>> {
+        self.select(card, 0x00, 0x04, file_id)
+    }
+
+    /// SELECT with the given P1/P2; P2=0C asks for no response data, so Le is omitted
+    pub(crate) fn select(&self, card: Option<&pcsc::Card>, p1: u8, p2: u8, id: &[u8]) -> Result<Vec<u8>> {
+        let mut command = vec![0x00, 0xA4, p1, p2, id.len() as u8];
+        command.extend_from_slice(id);
+        if p2 != 0x0C {
+            command.push(0x00);
+        }
+        
+        let result = self.exchange(card, &command, SELECT_RESPONSE_LENGTH, 3)?;
+        match (result.sw1, result.sw2) {
+            (0x90, 0x00) | (0x61, _) => Ok(result.data.to_vec()),
+            (0x6A, 0x82) => Err(error(FILE_NOT_FOUND, format!("File {} not found", hex(id)))),
+            (sw1, sw2) => Err(error(COMMAND_FAILED, format!("SELECT {} failed with status {:02X}{:02X}", hex(id), sw1, sw2))),
+        }
+    }
+
+    /// READ BINARY from offset 0 until `size` bytes, a short read, or the end of the file
+    pub(crate) fn read_binary_to_end(&self, card: Option<&pcsc::Card>, size: Option<u32>) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        loop {
+            let offset = data.len() as u32;
+            let wanted = match size {
+                Some(size) if offset >= size => break,
+                Some(size) => (size - offset).min(0xFF),
+                None => 0xFF,
+            };
+            if offset > MAX_BINARY_OFFSET {
+                break;
+            }
+            
+            let command = [0x00, 0xB0, (offset >> 8) as u8, offset as u8, wanted as u8];
+            let result = self.exchange(card, &command, wanted, 3)?;
+            match (result.sw1, result.sw2) {
+                (0x90, 0x00) | (0x61, _) => {}
+                // End of file reached before Le bytes; the data is still valid
+                (0x62, 0x82) => {
+                    data.extend_from_slice(&result.data);
+                    break;
+                }
+                // Reading past the end of a file of unknown size
+                _ if size.is_none() && offset > 0 => break,
+                (sw1, sw2) => {
+                    return Err(error(COMMAND_FAILED, format!("READ BINARY at offset {:04X} failed with status {:02X}{:02X}", offset, sw1, sw2)));
+                }
+            }
+            
+            data.extend_from_slice(&result.data);
+            if (result.data.len() as u32) < wanted {
+                break;
+            }
+        }
+        Ok(data)
+    }
+
+    /// read_binary on the locked handle
+    pub(crate) fn read_binary_range(&self, card: Option<&pcsc::Card>, offset: u32, length: u32, chunk_size: Option<u32>) -> Result<Vec<u8>> {
+        let chunk_size = chunk_size.unwrap_or(0xFF);
+        if !(1..=256).contains(&chunk_size) {
+            return Err(error(INVALID_ARGUMENT, format!("Chunk size must be 1 to 256, got {}", chunk_size)));
+        }
+        let end = offset.checked_add(length)
+            .filter(|end| *end <= 0x0100_0000)
+            .ok_or_else(|| error(INVALID_ARGUMENT, "Offset and length go past the largest addressable offset"))?;
+        
+        let mut data = Vec::with_capacity(length as usize);
+        let mut position = offset;
+        while position < end {
+            let odd = position > MAX_BINARY_OFFSET;
+            let wanted = if odd {
+                // Leave room in Le for the 53 81 xx wrapper
+                (end - position).min(chunk_size).min(253)
+            } else {
+                (end - position).min(chunk_size)
+            };
+            let command = if odd {
+                vec![0x00, 0xB1, 0x00, 0x00, 0x05, 0x54, 0x03, (position >> 16) as u8, (position >> 8) as u8, position as u8, (wanted + 3) as u8]
+            } else {
+                vec![0x00, 0xB0, (position >> 8) as u8, position as u8, wanted as u8]
+            };
+            let result = self.exchange(card, &command, wanted + 4, 3)?;
+            let chunk = if odd {
+                // B1 wraps the data in a discretionary data object (tag 53)
+                read_tlv(&result.data).filter(|(tag, _)| *tag == 0x53).map_or(&[][..], |(_, value)| value)
+            } else {
+                &result.data[..]
+            };
+            match (result.sw1, result.sw2) {
+                (0x90, 0x00) | (0x61, _) => {}
+                (0x62, 0x82) => {
+                    data.extend_from_slice(chunk);
+                    break;
+                }
+                (0x6B, 0x00) if !data.is_empty() => break,
+                (0x6A, 0x82) | (0x69, 0x86) => return Err(error(FILE_NOT_FOUND, "No EF is selected to read")),
+                (sw1, sw2) => {
+                    return Err(error(COMMAND_FAILED, format!("READ BINARY at offset {:06X} failed with status {:02X}{:02X}", position, sw1, sw2)));
+                }
+            }
+            
+            data.extend_from_slice(chunk);
+            if (chunk.len() as u32) < wanted {
+                break;
+            }
+            position += wanted;
+        }
+        Ok(data)
+    }
+}
+
+#[napi]
+impl Card {
+    /// SELECT an application by AID (`<cla> A4 04 00 <len> <aid> 00`), returning the FCI and status
+    ///
+    /// 61xx and 6Cxx are followed up like transmit. A status other than 90 00 isn't an error;
+    /// check `statusWord` (6A82 means the applet isn't on the card).
+    #[napi(catch_unwind)]
+    pub fn select_applet(&self, aid: Buffer, cla: Option<u8>) -> Result<TransmitResult> {
+        if !(5..=16).contains(&aid.len()) {
+            return Err(error(INVALID_ARGUMENT, format!("AID must be 5 to 16 bytes, got {}", aid.len())));
+        }
+        
+        let mut command = vec![cla.unwrap_or(0x00), 0xA4, 0x04, 0x00, aid.len() as u8];
+        command.extend_from_slice(&aid);
+        command.push(0x00);
+        
+        let card = self.lock()?;
+        self.exchange(card.handle(), &command, SELECT_RESPONSE_LENGTH, 3)
+    }
+
+    /// Read `length` bytes of the current EF from `offset`, in READ BINARY chunks of `chunk_size` (default 255)
+    ///
+    /// Offsets above 0x7FFF use READ BINARY with the odd instruction (B1) and an offset data object.
+    /// Stops early on a short read or end of file (6282 / 6B00 after some data); other statuses fail.
+    #[napi(catch_unwind)]
+    pub fn read_binary(&self, offset: u32, length: u32, chunk_size: Option<u32>) -> Result<Buffer> {
+        let card = self.lock()?;
+        Ok(Buffer::from(self.read_binary_range(card.handle(), offset, length, chunk_size)?))
+    }
+
+    /// SELECT an EF by file ID and read it, reporting the size declared in its FCP
+    #[napi(catch_unwind)]
+    pub fn read_file(&self, file_id: Buffer) -> Result<FileContents> {
+        let card = self.lock()?;
+        let fcp = self.select_file(card.handle(), &file_id)?;
+        let declared_size = fcp_file_size(&fcp);
+        let data = self.read_binary_to_end(card.handle(), declared_size)?;
+        
+        Ok(FileContents {
+            complete: declared_size.is_none_or(|size| size as usize == data.len()),
+            data: Buffer::from(data),
+            declared_size,
+        })
+    }
+}
@@ -0,0 +1,102 @@
+// Reader groups (SCardListReaders with mszGroups, SCardListReaderGroups), which the pcsc crate doesn't wrap
+
+use crate::error::{error, pcsc_error, CONTEXT_FAILED, INVALID_ARGUMENT};
+use crate::reader::probed_list;
+use napi::Result;
+use pcsc::Scope;
+use pcsc_sys as ffi;
+use std::ffi::CString;
+use std::ptr;
+
+/// A context of our own for one call; the pcsc crate doesn't expose its Context's handle
+struct RawContext(ffi::SCARDCONTEXT);
+
+impl RawContext {
+    fn establish(scope: Scope) -> Result<Self> {
+        let mut handle: ffi::SCARDCONTEXT = 0;
+        // SAFETY: the reserved pointers may be null and `handle` outlives the call
+        check(unsafe { ffi::SCardEstablishContext(scope as ffi::DWORD, ptr::null(), ptr::null(), &mut handle) })
+            .map_err(|e| pcsc_error(CONTEXT_FAILED, "Failed to establish PC/SC context", e))?;
+        Ok(Self(handle))
+    }
+}
+
+impl Drop for RawContext {
+    fn drop(&mut self) {
+        // SAFETY: the handle came from SCardEstablishContext and is released only here
+        unsafe { ffi::SCardReleaseContext(self.0) };
+    }
+}
+
+/// Names of the readers in `group` (e.g. "SCard$DefaultReaders")
+pub(crate) fn reader_names_in_group(scope: Scope, group: &str) -> Result<Vec<CString>> {
+    let group = CString::new(group)
+        .map_err(|e| error(INVALID_ARGUMENT, format!("Invalid reader group name: {}", e)))?;
+    // mszGroups is a multi-string: the name's own NUL plus the terminating one
+    let mut groups = group.into_bytes_with_nul();
+    groups.push(0);
+
+    let ctx = RawContext::establish(scope)?;
+    let list = |buffer: *mut u8, len: &mut ffi::DWORD| {
+        // SAFETY: `groups` is a terminated multi-string, and `buffer` is null (length probe)
+        // or holds `len` bytes
+        check(unsafe { ffi::SCardListReaders(ctx.0, groups.as_ptr().cast(), buffer.cast(), len) })
+    };
+    probed_list(
+        || {
+            let mut len = 0;
+            list(ptr::null_mut(), &mut len).map(|()| len as usize)
+        },
+        |buffer| {
+            let mut len = buffer.len() as ffi::DWORD;
+            list(buffer.as_mut_ptr(), &mut len).map(|()| parse_multi_string(&buffer[..len as usize]))
+        },
+    )
+}
+
+/// Names of the reader groups defined on this system
+pub(crate) fn reader_group_names(scope: Scope) -> Result<Vec<CString>> {
+    let ctx = RawContext::establish(scope)?;
+    let list = |buffer: *mut u8, len: &mut ffi::DWORD| {
+        // SAFETY: `buffer` is null (length probe) or holds `len` bytes
+        check(unsafe { ffi::SCardListReaderGroups(ctx.0, buffer.cast(), len) })
+    };
+    probed_list(
+        || {
+            let mut len = 0;
+            list(ptr::null_mut(), &mut len).map(|()| len as usize)
+        },
+        |buffer| {
+            let mut len = buffer.len() as ffi::DWORD;
+            list(buffer.as_mut_ptr(), &mut len).map(|()| parse_multi_string(&buffer[..len as usize]))
+        },
+    )
+}
+
+/// Split a PC/SC multi-string (NUL-separated, ended by an empty string) into its entries
+pub(crate) fn parse_multi_string(buffer: &[u8]) -> Vec<CString> {
+    buffer.split(|&b| b == 0)
+        .take_while(|entry| !entry.is_empty())
+        .filter_map(|entry| CString::new(entry).ok())
+        .collect()
+}
+
+fn check(code: ffi::LONG) -> pcsc::Result<()> {
+    if code == ffi::SCARD_S_SUCCESS {
+        Ok(())
+    } else {
+        Err(pcsc::Error::from_raw(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_multi_string_stops_at_the_empty_entry() {
+        let names = parse_multi_string(b"SCard$DefaultReaders\0Thai ID\0\0stale\0");
+        assert_eq!(names, [c"SCard$DefaultReaders", c"Thai ID"]);
+        assert!(parse_multi_string(b"\0").is_empty());
+    }
+}
@@ -0,0 +1,43 @@
+// ICAO 9303 LDS (ePassport-style) file navigation
+//
+// Only plain SELECT/READ BINARY; documents that require BAC/PACE need secure
+// messaging layered on top by the caller.
+
+use crate::card::Card;
+use crate::error::{error, INVALID_ARGUMENT};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// LDS applet AID
+const LDS_AID: [u8; 7] = [0xA0, 0x00, 0x00, 0x02, 0x47, 0x10, 0x01];
+
+/// EF.COM file ID
+const EF_COM: [u8; 2] = [0x01, 0x1E];
+
+impl Card {
+    /// Select the LDS applet, then the EF, and read it to the end
+    fn read_lds_file(&self, file_id: [u8; 2]) -> Result<Buffer> {
+        let card = self.lock()?;
+        self.select(card.handle(), 0x04, 0x0C, &LDS_AID)?;
+        self.select(card.handle(), 0x02, 0x0C, &file_id)?;
+        Ok(Buffer::from(self.read_binary_to_end(card.handle(), None)?))
+    }
+}
+
+#[napi]
+impl Card {
+    /// Read EF.COM from the LDS applet (raw bytes)
+    #[napi(catch_unwind)]
+    pub fn read_ef_com(&self) -> Result<Buffer> {
+        self.read_lds_file(EF_COM)
+    }
+
+    /// Read data group `dg_number` (1-16) from the LDS applet (raw bytes)
+    #[napi(catch_unwind)]
+    pub fn read_dg(&self, dg_number: u8) -> Result<Buffer> {
+        if !(1..=16).contains(&dg_number) {
+            return Err(error(INVALID_ARGUMENT, format!("Data group must be 1-16, got {}", dg_number)));
+        }
+        self.read_lds_file([0x01, dg_number])
+    }
+}
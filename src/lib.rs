@@ -1,12 +1,26 @@
 // Main library module - re-exports all public APIs
 
+mod error;
 mod types;
 mod reader;
+mod groups;
+mod monitor;
 mod card;
+mod connection;
+mod thai_id;
+mod file;
+mod lds;
+mod contactless;
+mod ndef;
+mod pinpad;
+mod pin;
 mod utils;
+mod logger;
+mod throttle;
+mod timeout;
 
 // Re-export types
-pub use types::{CardStatus, TransmitResult};
+pub use types::{AtrInfo, CardStatus, EnvironmentInfo, FileContents, FullCardStatus, Protocol, ProtocolParams, ReaderChange, ReaderDetail, ReaderFeature, ReaderWithStatus, ShareMode, SignedBlock, SignedBlockLayout, ThaiIdAddress, ThaiIdCard, ThaiIdData, TransmitResult, VerifyResult};
 
 // Re-export reader
 pub use reader::SmartCardReader;
@@ -15,4 +29,8 @@ pub use reader::SmartCardReader;
 pub use card::Card;
 
 // Re-export utils
-pub use utils::get_version;
+pub use utils::{atr_equal, build_apdu, ctl_code, decode_tis620, decode_tis620_fields, describe_status, from_hex, get_environment_info, get_version, match_atr, parse_atr, thai_date_to_iso, thai_id_generation, to_hex, validate_thai_cid};
+
+// Re-export logger
+pub use logger::{set_debug, set_logger};
+pub use thai_id::thai_id_to_json;
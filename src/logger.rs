@@ -0,0 +1,51 @@
+use crate::error::{error, LOCK_FAILED};
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::JsFunction;
+use napi_derive::napi;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+static LOGGER: Mutex<Option<ThreadsafeFunction<String, ErrorStrategy::Fatal>>> = Mutex::new(None);
+static DEBUG: AtomicBool = AtomicBool::new(false);
+
+/// Set a callback that receives log messages (e.g. dry-run APDUs), or null to remove it
+#[napi(catch_unwind, ts_args_type = "callback: ((message: string) => void) | null")]
+pub fn set_logger(env: Env, callback: Option<JsFunction>) -> Result<()> {
+    let logger = match callback {
+        Some(callback) => {
+            let mut logger: ThreadsafeFunction<String, ErrorStrategy::Fatal> = callback
+                .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<String>| Ok(vec![ctx.value]))?;
+            // A logger alone shouldn't keep the event loop alive
+            logger.unref(&env)?;
+            Some(logger)
+        }
+        None => None,
+    };
+    
+    *LOGGER.lock()
+        .map_err(|e| error(LOCK_FAILED, format!("Failed to lock logger: {}", e)))? = logger;
+    Ok(())
+}
+
+/// Send a message to the registered logger, if any
+pub(crate) fn log(message: String) {
+    if let Ok(logger) = LOGGER.lock() {
+        if let Some(logger) = logger.as_ref() {
+            logger.call(message, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+    }
+}
+
+/// Log every APDU exchange (command, response, status word, elapsed time) to the logger
+///
+/// Off by default: APDUs can carry personal data and PINs.
+#[napi(catch_unwind)]
+pub fn set_debug(enabled: bool) {
+    DEBUG.store(enabled, Ordering::SeqCst);
+}
+
+/// Whether APDU exchanges should be logged
+pub(crate) fn debug_enabled() -> bool {
+    DEBUG.load(Ordering::SeqCst)
+}
@@ -0,0 +1,94 @@
+// Background card insertion/removal monitor for SmartCardReader
+
+use crate::error::{error, pcsc_error, CONTEXT_FAILED, INVALID_READER_NAME};
+use crate::logger;
+use crate::reader::{reader_card_status, SmartCardReader};
+use crate::types::CardStatus;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::JsFunction;
+use napi_derive::napi;
+use pcsc::{Context, ReaderState, State};
+use std::ffi::CString;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Upper bound on each blocking get_status_change, so a stop that races the call is still seen
+const MONITOR_POLL: Duration = Duration::from_secs(1);
+
+/// A running monitor thread with its own context, so cancelling it leaves other waits alone
+pub(crate) struct Monitor {
+    ctx: Context,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = self.ctx.cancel();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[napi]
+impl SmartCardReader {
+    /// Call `callback` with the card status now and after every insertion or removal,
+    /// until stop_monitor (replaces any running monitor)
+    #[napi(catch_unwind, ts_args_type = "readerName: string, callback: (status: CardStatus) => void")]
+    pub fn start_monitor(&self, reader_name: String, callback: JsFunction) -> Result<()> {
+        self.stop_monitor();
+        
+        let reader = CString::new(reader_name)
+            .map_err(|e| error(INVALID_READER_NAME, format!("Failed to convert reader name: {}", e)))?;
+        let ctx = Context::establish(self.scope)
+            .map_err(|e| pcsc_error(CONTEXT_FAILED, "Failed to establish PC/SC context", e))?;
+        let callback: ThreadsafeFunction<CardStatus, ErrorStrategy::Fatal> = callback
+            .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<CardStatus>| Ok(vec![ctx.value]))?;
+        
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let ctx = ctx.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || run_monitor(&ctx, reader, &stop, &callback))
+        };
+        
+        *self.monitor.lock().unwrap_or_else(|e| e.into_inner()) = Some(Monitor { ctx, stop, thread: Some(thread) });
+        Ok(())
+    }
+
+    /// Stop the monitor started by start_monitor, waiting for its thread to exit
+    #[napi(catch_unwind)]
+    pub fn stop_monitor(&self) {
+        // Taken out first so the thread is joined without holding the lock
+        let monitor = self.monitor.lock().unwrap_or_else(|e| e.into_inner()).take();
+        drop(monitor);
+    }
+}
+
+fn run_monitor(ctx: &Context, reader: CString, stop: &AtomicBool, callback: &ThreadsafeFunction<CardStatus, ErrorStrategy::Fatal>) {
+    let mut reader_states = vec![ReaderState::new(reader, State::UNAWARE)];
+    let mut last_present = None;
+    while !stop.load(Ordering::SeqCst) {
+        match ctx.get_status_change(MONITOR_POLL, &mut reader_states) {
+            Ok(()) => {}
+            Err(pcsc::Error::Timeout) => continue,
+            Err(pcsc::Error::Cancelled) => break,
+            Err(e) => {
+                logger::log(format!("Monitor stopped: {}", e));
+                break;
+            }
+        }
+        
+        let status = reader_card_status(&reader_states[0]);
+        if last_present != Some(status.present) {
+            last_present = Some(status.present);
+            callback.call(status, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+        reader_states[0].sync_current_state();
+    }
+}
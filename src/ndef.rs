@@ -0,0 +1,59 @@
+// NFC Forum Type 4 Tag NDEF reading (NDEF application, Capability Container, NDEF file)
+
+use crate::card::Card;
+use crate::error::{error, has_reason, FILE_NOT_FOUND, INVALID_RESPONSE, NDEF_NOT_FOUND};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// AID of the NDEF Tag Application (mapping version 2.0)
+const NDEF_AID: [u8; 7] = [0xD2, 0x76, 0x00, 0x00, 0x85, 0x01, 0x01];
+
+/// File ID of the Capability Container
+const CC_FILE_ID: [u8; 2] = [0xE1, 0x03];
+
+/// CC length up to and including the NDEF File Control TLV
+const CC_LENGTH: u32 = 15;
+
+/// Tag of the NDEF File Control TLV in the CC
+const NDEF_FILE_CONTROL_TAG: u8 = 0x04;
+
+#[napi]
+impl Card {
+    /// Read the NDEF message of a Type 4 tag, without its 2-byte NLEN prefix
+    ///
+    /// Selects the NDEF application and the Capability Container, then the NDEF file it
+    /// points to, and reads NLEN bytes in chunks of the tag's MLe. Fails with NDEF_NOT_FOUND
+    /// when the tag has no NDEF application. An empty tag (NLEN 0) gives an empty buffer.
+    #[napi(catch_unwind)]
+    pub fn read_ndef(&self) -> Result<Buffer> {
+        let message = self.read_then_release(None, |card| {
+            self.select(card, 0x04, 0x00, &NDEF_AID).map_err(|e| {
+                if has_reason(&e, FILE_NOT_FOUND) {
+                    error(NDEF_NOT_FOUND, "Tag has no NDEF application")
+                } else {
+                    e
+                }
+            })?;
+            
+            self.select(card, 0x00, 0x0C, &CC_FILE_ID)?;
+            let cc = self.read_binary_range(card, 0, CC_LENGTH, None)?;
+            let (max_le, file_id) = match cc[..] {
+                [_, _, _, le_hi, le_lo, _, _, NDEF_FILE_CONTROL_TAG, 0x06, id_hi, id_lo, ..] => {
+                    (u16::from_be_bytes([le_hi, le_lo]) as u32, [id_hi, id_lo])
+                }
+                _ => return Err(error(INVALID_RESPONSE, "Capability Container has no NDEF File Control TLV")),
+            };
+            
+            self.select(card, 0x00, 0x0C, &file_id)?;
+            let nlen = match self.read_binary_range(card, 0, 2, None)?[..] {
+                [hi, lo] => u16::from_be_bytes([hi, lo]) as u32,
+                _ => return Err(error(INVALID_RESPONSE, "NDEF file is shorter than its length field")),
+            };
+            if nlen == 0 {
+                return Ok(Vec::new());
+            }
+            self.read_binary_range(card, 2, nlen, Some(max_le.clamp(1, 0xFF)))
+        })?;
+        Ok(Buffer::from(message))
+    }
+}
@@ -0,0 +1,90 @@
+// PIN verification (ISO 7816-4 VERIFY)
+
+use crate::card::{Card, SELECT_RESPONSE_LENGTH};
+use crate::error::{error, COMMAND_FAILED, FILE_NOT_FOUND, INVALID_ARGUMENT, PIN_BLOCKED, SELECT_FAILED, WRONG_PIN};
+use crate::types::VerifyResult;
+use crate::utils::hex;
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+impl Card {
+    /// VERIFY on the locked handle; see verify_pin
+    pub(crate) fn verify(&self, card: Option<&pcsc::Card>, p2: u8, pin: &[u8]) -> Result<VerifyResult> {
+        if pin.len() > 0xFF {
+            return Err(error(INVALID_ARGUMENT, format!("PIN too long: {} bytes (max 255)", pin.len())));
+        }
+        let mut command = vec![0x00, 0x20, 0x00, p2];
+        if !pin.is_empty() {
+            command.push(pin.len() as u8);
+            command.extend_from_slice(pin);
+        }
+        let result = self.exchange_redacted(card, &command, 2, 0, 5);
+        command.fill(0);
+        let result = result?;
+        verify_result(result.sw1, result.sw2)
+    }
+}
+
+#[napi]
+impl Card {
+    /// Send VERIFY (`00 20 00 <p2> <len> <pin>`) and report the outcome
+    ///
+    /// 63Cx is a wrong PIN with x tries left and 6983 a blocked PIN; neither is an error.
+    /// An empty PIN only asks for the retry counter (90 00 if already verified). The PIN is
+    /// left out of dry-run and debug logs, and the command buffer is wiped after sending.
+    #[napi(catch_unwind)]
+    pub fn verify_pin(&self, p2: u8, pin: Buffer) -> Result<VerifyResult> {
+        let card = self.lock()?;
+        self.verify(card.handle(), p2, &pin)
+    }
+
+    /// SELECT an application, VERIFY its PIN and READ BINARY from the current EF, in one transaction
+    ///
+    /// Each step fails with its own code: FILE_NOT_FOUND for a missing application or EF,
+    /// SELECT_FAILED for any other SELECT status, WRONG_PIN (message has the tries left, when
+    /// reported) and PIN_BLOCKED. `pin_reference` is the VERIFY P2 (default 0x80).
+    #[napi(catch_unwind)]
+    pub fn read_protected(&self, aid: Buffer, pin: Buffer, offset: u32, length: u32, pin_reference: Option<u8>) -> Result<Buffer> {
+        if !(5..=16).contains(&aid.len()) {
+            return Err(error(INVALID_ARGUMENT, format!("AID must be 5 to 16 bytes, got {}", aid.len())));
+        }
+        
+        let data = self.read_then_release(None, |card| {
+            let mut select = vec![0x00, 0xA4, 0x04, 0x00, aid.len() as u8];
+            select.extend_from_slice(&aid);
+            select.push(0x00);
+            let selected = self.exchange(card, &select, SELECT_RESPONSE_LENGTH, 3)?;
+            match (selected.sw1, selected.sw2) {
+                (0x90, 0x00) | (0x61, _) => {}
+                (0x6A, 0x82) => return Err(error(FILE_NOT_FOUND, format!("Application {} not found", hex(&aid)))),
+                (sw1, sw2) => return Err(error(SELECT_FAILED, format!("SELECT {} failed with status {:02X}{:02X}", hex(&aid), sw1, sw2))),
+            }
+            
+            let verified = self.verify(card, pin_reference.unwrap_or(0x80), &pin)?;
+            if verified.blocked {
+                return Err(error(PIN_BLOCKED, "PIN is blocked"));
+            }
+            if !verified.verified {
+                return Err(match verified.tries_remaining {
+                    Some(tries) => error(WRONG_PIN, format!("Wrong PIN ({} tries left)", tries)),
+                    None => error(WRONG_PIN, "Wrong PIN"),
+                });
+            }
+            
+            self.read_binary_range(card, offset, length, None)
+        })?;
+        Ok(Buffer::from(data))
+    }
+}
+
+/// Interpret the status word of a PIN verification; unexpected statuses fail with COMMAND_FAILED
+pub(crate) fn verify_result(sw1: u8, sw2: u8) -> Result<VerifyResult> {
+    let (verified, blocked, tries_remaining) = match (sw1, sw2) {
+        (0x90, 0x00) => (true, false, None),
+        (0x63, 0xC0..=0xCF) => (false, sw2 == 0xC0, Some((sw2 & 0x0F) as u32)),
+        (0x63, 0x00) => (false, false, None),
+        (0x69, 0x83) => (false, true, Some(0)),
+        _ => return Err(error(COMMAND_FAILED, format!("VERIFY failed with status {:02X}{:02X}", sw1, sw2))),
+    };
+    Ok(VerifyResult { verified, blocked, tries_remaining, status_word: u16::from_be_bytes([sw1, sw2]) })
+}
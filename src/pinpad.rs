@@ -0,0 +1,58 @@
+// PC/SC part 10 reader features (pinpad readers)
+
+use crate::card::Card;
+use crate::error::{error, pcsc_error, CANCELLED, CONTROL_FAILED, INVALID_RESPONSE, TIMEOUT};
+use crate::pin::verify_result;
+use crate::types::{ReaderFeature, VerifyResult};
+use napi::bindgen_prelude::*;
+use napi_derive::napi;
+
+/// CM_IOCTL_GET_FEATURE_REQUEST, SCARD_CTL_CODE(3400)
+fn get_feature_request() -> u32 {
+    pcsc::ctl_code(3400)
+}
+
+#[napi]
+impl Card {
+    /// List the reader's PC/SC part 10 features with their control codes (CM_IOCTL_GET_FEATURE_REQUEST)
+    ///
+    /// Readers without part 10 support give an empty list.
+    #[napi(catch_unwind)]
+    pub fn get_features(&self) -> Result<Vec<ReaderFeature>> {
+        let response = match self.send_control(get_feature_request(), &[])? {
+            Ok(response) => response,
+            Err(pcsc::Error::UnsupportedFeature | pcsc::Error::InvalidParameter) => return Ok(Vec::new()),
+            Err(e) => return Err(pcsc_error(CONTROL_FAILED, "Failed to read reader features", e)),
+        };
+        Ok(parse_features(&response))
+    }
+
+    /// Verify a PIN entered on the reader's own keypad (FEATURE_VERIFY_PIN_DIRECT)
+    ///
+    /// `control_code` comes from get_features and `pin_verify_structure` is the PC/SC part 10
+    /// PIN_VERIFY_STRUCTURE, including the VERIFY APDU. The outcome is reported as for verify_pin;
+    /// a keypad timeout (64 00) fails with TIMEOUT and a cancelled entry (64 01) with CANCELLED.
+    #[napi(catch_unwind)]
+    pub fn verify_pin_direct(&self, control_code: u32, pin_verify_structure: Buffer) -> Result<VerifyResult> {
+        let response = self.send_control(control_code, &pin_verify_structure)?
+            .map_err(|e| pcsc_error(CONTROL_FAILED, "Failed to start PIN entry", e))?;
+        match response[..] {
+            [] if self.dry_run => verify_result(0x90, 0x00),
+            [0x64, 0x00] => Err(error(TIMEOUT, "PIN entry timed out on the reader")),
+            [0x64, 0x01] => Err(error(CANCELLED, "PIN entry was cancelled on the reader")),
+            [sw1, sw2] => verify_result(sw1, sw2),
+            _ => Err(error(INVALID_RESPONSE, format!("Unexpected PIN entry response of {} bytes", response.len()))),
+        }
+    }
+}
+
+/// Parse the TLV list of features: tag, length 4, big-endian control code
+fn parse_features(response: &[u8]) -> Vec<ReaderFeature> {
+    response.chunks_exact(6)
+        .filter(|tlv| tlv[1] == 4)
+        .map(|tlv| ReaderFeature {
+            tag: tlv[0],
+            control_code: u32::from_be_bytes([tlv[2], tlv[3], tlv[4], tlv[5]]),
+        })
+        .collect()
+}
@@ -1,138 +1,664 @@
-use crate::types::CardStatus;
+use crate::error::{connect_error, error, has_reason, pcsc_error, CONTEXT_FAILED, INVALID_ARGUMENT, INVALID_HANDLE, INVALID_READER_NAME, LIST_READERS_FAILED, LOCK_FAILED, NO_SERVICE, READER_NOT_FOUND, STATUS_FAILED};
+use crate::groups;
+use crate::monitor::Monitor;
+use crate::throttle::Throttle;
+use crate::timeout::with_timeout;
+use crate::types::{CardStatus, Protocol, ReaderChange, ReaderDetail, ReaderWithStatus, ShareMode};
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
-use pcsc::{Context, ReaderState, Scope, ShareMode, Protocols, State};
-use std::ffi::CString;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use crate::card::{card_atr, to_protocols, to_share_mode};
+use pcsc::{Context, ReaderState, Scope, State};
+use std::ffi::{CStr, CString};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Extra time given to get_status_change before the worker is cancelled (some drivers overrun the timeout)
+const WAIT_TIMEOUT_GRACE: Duration = Duration::from_secs(1);
+
+/// Length probes tried when the reader list grows while it is being read
+const LIST_READERS_ATTEMPTS: u32 = 3;
 
 #[napi]
 pub struct SmartCardReader {
     ctx: Arc<Mutex<Context>>,
+    dry_run: AtomicBool,
+    throttle: Arc<Throttle>,
+    status_timeout_ms: AtomicU32,
+    pub(crate) monitor: Mutex<Option<Monitor>>,
+    pub(crate) scope: Scope,
+    /// Reader names from the last listing; lookups refresh it on a miss
+    readers: Mutex<Vec<CString>>,
+    /// State of the reader last polled by is_card_present, reused while the same reader is polled
+    presence: Mutex<Option<ReaderState>>,
+    /// Contexts of the waits in progress, for cancel
+    waits: Mutex<Vec<Arc<Context>>>,
 }
 
 #[napi]
 impl SmartCardReader {
-    #[napi(constructor)]
-    pub fn new() -> Result<Self> {
-        let ctx = Context::establish(Scope::User)
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to establish PC/SC context: {}", e)))?;
+    #[napi(constructor, catch_unwind)]
+    pub fn new(warm_up: Option<bool>, scope: Option<u32>) -> Result<Self> {
+        let scope = to_scope(scope.unwrap_or(0));
+        let ctx = establish(scope)?;
+        
+        if warm_up.unwrap_or(false) {
+            // The first call on a fresh context can be slow or fail transiently; prime it and ignore the result
+            let _ = reader_names(&ctx);
+        }
         
         Ok(Self {
+            readers: Mutex::default(),
+            presence: Mutex::default(),
+            waits: Mutex::default(),
             ctx: Arc::new(Mutex::new(ctx)),
+            dry_run: AtomicBool::new(false),
+            throttle: Arc::default(),
+            status_timeout_ms: AtomicU32::new(0),
+            monitor: Mutex::default(),
+            scope,
         })
     }
 
-    #[napi]
-    pub fn list_readers(&self) -> Result<Vec<String>> {
-        let ctx = self.ctx.lock()
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to lock context: {}", e)))?;
-        
-        let mut buffer = vec![0u8; 1024];
-        let readers = ctx.list_readers(&mut buffer)
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to list readers: {}", e)))?;
-        
-        let reader_vec: Vec<_> = readers.collect();
-        Ok(reader_vec.iter().map(|r| r.to_string_lossy().to_string()).collect())
+    /// Default timeout for get_status when none is passed (some drivers hang on a zero timeout)
+    #[napi(catch_unwind)]
+    pub fn set_status_timeout_ms(&self, timeout_ms: u32) {
+        self.status_timeout_ms.store(timeout_ms, Ordering::SeqCst);
     }
 
-    #[napi]
-    pub fn get_status(&self, reader_name: String) -> Result<CardStatus> {
-        let ctx = self.ctx.lock()
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to lock context: {}", e)))?;
-        
-        let mut buffer = vec![0u8; 1024];
-        let readers = ctx.list_readers(&mut buffer)
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to list readers: {}", e)))?;
-        
-        let reader_vec: Vec<_> = readers.collect();
-        let reader = reader_vec.iter()
-            .find(|r| r.to_string_lossy() == reader_name)
-            .ok_or_else(|| napi::Error::new(napi::Status::GenericFailure, format!("Reader not found: {}", reader_name)))?;
+    #[napi(catch_unwind)]
+    pub fn get_status_timeout_ms(&self) -> u32 {
+        self.status_timeout_ms.load(Ordering::SeqCst)
+    }
+
+    /// Minimum spacing between an operation that failed and the next one, on this reader and its cards
+    #[napi(catch_unwind)]
+    pub fn set_min_op_interval_ms(&self, interval_ms: u32) {
+        self.throttle.set_min_interval(Duration::from_millis(interval_ms as u64));
+    }
+
+    /// Make connect return dry-run cards that log APDUs instead of touching the reader
+    #[napi(catch_unwind)]
+    pub fn set_dry_run(&self, enabled: bool) {
+        self.dry_run.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Whether the PC/SC context is still usable (it isn't once the smart card service restarts)
+    #[napi(catch_unwind)]
+    pub fn is_valid(&self) -> Result<bool> {
+        Ok(self.lock_context()?.is_valid().is_ok())
+    }
+
+    /// Replace the PC/SC context with a newly established one
+    ///
+    /// Operations already do this by themselves when they find the context invalid.
+    #[napi(catch_unwind)]
+    pub fn reestablish(&self) -> Result<()> {
+        let mut ctx = self.lock_context()?;
+        *ctx = establish(self.scope)?;
+        Ok(())
+    }
+
+    /// List readers, or only those in reader `group` (e.g. "SCard$DefaultReaders")
+    ///
+    /// Without a group this also refreshes the cached reader list; a group listing leaves it alone.
+    #[napi(catch_unwind)]
+    pub fn list_readers(&self, group: Option<String>) -> Result<Vec<String>> {
+        let Some(group) = group else {
+            return self.refresh_readers();
+        };
+        let readers = groups::reader_names_in_group(self.scope, &group)?;
         
-        let reader_cstr = CString::new(reader.to_string_lossy().as_ref())
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to convert reader name: {}", e)))?;
-        let mut reader_states = vec![ReaderState::new(reader_cstr, State::UNAWARE)];
-        ctx.get_status_change(Duration::from_secs(0), &mut reader_states)
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to get status: {:?}", e)))?;
+        Ok(readers.iter().map(|r| r.to_string_lossy().to_string()).collect())
+    }
+
+    /// List the reader groups defined on this system
+    #[napi(catch_unwind)]
+    pub fn list_reader_groups(&self) -> Result<Vec<String>> {
+        let groups = groups::reader_group_names(self.scope)?;
         
-        let state = reader_states[0].event_state();
+        Ok(groups.iter().map(|g| g.to_string_lossy().to_string()).collect())
+    }
+
+    /// Re-list the readers and refresh the cached list used by connect/get_status/wait_for_card
+    #[napi(catch_unwind)]
+    pub fn refresh_readers(&self) -> Result<Vec<String>> {
+        let readers = self.with_context(|ctx| self.refresh_cache(ctx))?;
         
-        Ok(CardStatus {
-            present: state.contains(State::PRESENT),
-            empty: state.contains(State::EMPTY),
-            mute: state.contains(State::MUTE),
-            atr: None,
+        Ok(readers.iter().map(|r| r.to_string_lossy().to_string()).collect())
+    }
+
+    /// List readers with card presence and ATR, using a single get_status_change for all of them
+    #[napi(catch_unwind)]
+    pub fn list_readers_detailed(&self) -> Result<Vec<ReaderDetail>> {
+        Ok(self.all_reader_states()?.iter().map(|reader_state| {
+            let present = reader_state.event_state().contains(State::PRESENT);
+            ReaderDetail {
+                name: reader_state.name().to_string_lossy().to_string(),
+                present,
+                atr: if present && !reader_state.atr().is_empty() {
+                    Some(Buffer::from(reader_state.atr().to_vec()))
+                } else {
+                    None
+                },
+            }
+        }).collect())
+    }
+
+    /// List readers with their full card status, using a single get_status_change for all of them
+    #[napi(catch_unwind)]
+    pub fn list_readers_with_status(&self) -> Result<Vec<ReaderWithStatus>> {
+        Ok(self.all_reader_states()?.iter().map(|reader_state| ReaderWithStatus {
+            name: reader_state.name().to_string_lossy().to_string(),
+            status: reader_card_status(reader_state),
+        }).collect())
+    }
+
+    #[napi(catch_unwind)]
+    pub fn get_status(&self, reader_name: String, timeout_ms: Option<u32>) -> Result<CardStatus> {
+        let timeout = Duration::from_millis(timeout_ms.unwrap_or_else(|| self.get_status_timeout_ms()) as u64);
+        self.with_context(|ctx| {
+            let reader_cstr = self.find_reader(ctx, &reader_name)?;
+            let mut reader_states = vec![ReaderState::new(reader_cstr, State::UNAWARE)];
+            ctx.get_status_change(timeout, &mut reader_states)
+                .map_err(|e| pcsc_error(STATUS_FAILED, "Failed to get status", e))?;
+            
+            Ok(reader_card_status(&reader_states[0]))
         })
     }
 
-    #[napi]
-    pub fn connect(&self, reader_name: String, share_mode: u32, preferred_protocols: Option<u32>) -> Result<crate::card::Card> {
-        let ctx = self.ctx.lock()
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to lock context: {}", e)))?;
+    /// Connect to the card in a reader
+    ///
+    /// A sharing violation (another application holds the card) is retried up to `max_retries`
+    /// times, `retry_delay_ms` (default 100) apart; other errors fail at once.
+    #[napi(catch_unwind)]
+    pub fn connect(&self, reader_name: String, share_mode: ShareMode, preferred_protocols: Option<Protocol>, max_retries: Option<u32>, retry_delay_ms: Option<u32>) -> Result<crate::card::Card> {
+        if self.dry_run.load(Ordering::SeqCst) {
+            return Ok(crate::card::Card::dry_run());
+        }
         
-        let mut buffer = vec![0u8; 1024];
-        let readers = ctx.list_readers(&mut buffer)
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to list readers: {}", e)))?;
+        let share_mode = to_share_mode(share_mode);
+        let protocols = to_protocols(preferred_protocols, share_mode);
         
-        let reader_vec: Vec<_> = readers.collect();
-        let reader = reader_vec.iter()
-            .find(|r| r.to_string_lossy() == reader_name)
-            .ok_or_else(|| napi::Error::new(napi::Status::GenericFailure, format!("Reader not found: {}", reader_name)))?;
+        let retry_delay = Duration::from_millis(retry_delay_ms.unwrap_or(100) as u64);
+        let card = self.with_context(|ctx| {
+            let reader = self.find_reader(ctx, &reader_name)?;
+            let mut retries_left = max_retries.unwrap_or(0);
+            loop {
+                self.throttle.wait();
+                let card = ctx.connect(&reader, share_mode, protocols);
+                let retry = retries_left > 0 && matches!(card, Err(pcsc::Error::SharingViolation));
+                let card = card.map_err(connect_error);
+                self.throttle.record(&card);
+                if !retry {
+                    return card;
+                }
+                retries_left -= 1;
+                std::thread::sleep(retry_delay);
+            }
+        })?;
         
-        let share_mode = match share_mode {
-            0 => ShareMode::Shared,
-            1 => ShareMode::Exclusive,
-            _ => ShareMode::Direct,
-        };
+        let atr = card_atr(&card);
         
-        let protocols = match preferred_protocols {
-            Some(0) => Protocols::T0,
-            Some(1) => Protocols::T1,
-            Some(2) => Protocols::RAW,
-            _ => Protocols::ANY,
-        };
+        Ok(crate::card::Card::new(card, atr, share_mode, self.throttle.clone()))
+    }
+
+    /// Connect with `preferred_share_mode`, falling back to `fallback_share_mode` when another
+    /// application holds the card (sharing violation)
+    ///
+    /// The mode obtained is reported by the card's get_share_mode.
+    #[napi(catch_unwind)]
+    pub fn connect_preferred(&self, reader_name: String, preferred_share_mode: ShareMode, fallback_share_mode: ShareMode, preferred_protocols: Option<Protocol>) -> Result<crate::card::Card> {
+        if self.dry_run.load(Ordering::SeqCst) {
+            return Ok(crate::card::Card::dry_run());
+        }
         
-        let card = ctx.connect(&*reader, share_mode, protocols)
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to connect to card: {}", e)))?;
+        let preferred = to_share_mode(preferred_share_mode);
+        let (card, share_mode) = self.with_context(|ctx| {
+            let reader = self.find_reader(ctx, &reader_name)?;
+            self.throttle.wait();
+            let card = match ctx.connect(&reader, preferred, to_protocols(preferred_protocols, preferred)) {
+                Err(pcsc::Error::SharingViolation) => {
+                    let fallback = to_share_mode(fallback_share_mode);
+                    ctx.connect(&reader, fallback, to_protocols(preferred_protocols, fallback))
+                        .map(|card| (card, fallback))
+                }
+                card => card.map(|card| (card, preferred)),
+            }.map_err(connect_error);
+            self.throttle.record(&card);
+            card
+        })?;
         
-        let atr = None;
+        let atr = card_atr(&card);
         
-        Ok(crate::card::Card { 
-            inner: Arc::new(Mutex::new(card)),
-            atr,
+        Ok(crate::card::Card::new(card, atr, share_mode, self.throttle.clone()))
+    }
+
+    /// Whether a card is in the reader, for tight polling loops
+    ///
+    /// Polling the same reader reuses its cached name and reader state, so repeated calls
+    /// only cost one zero-timeout get_status_change.
+    #[napi(catch_unwind)]
+    pub fn is_card_present(&self, reader_name: String) -> Result<bool> {
+        self.with_context(|ctx| {
+            let mut presence = self.presence.lock().unwrap_or_else(|e| e.into_inner());
+            let reader_state = match &mut *presence {
+                Some(reader_state) if reader_state.name().to_bytes() == reader_name.as_bytes() => reader_state,
+                slot => slot.insert(ReaderState::new(self.find_reader(ctx, &reader_name)?, State::UNAWARE)),
+            };
+            // UNAWARE as the current state makes the call return at once with the event state
+            if let Err(e) = ctx.get_status_change(Duration::ZERO, std::slice::from_mut(reader_state)) {
+                *presence = None;
+                return Err(pcsc_error(STATUS_FAILED, "Failed to get status", e));
+            }
+            Ok(reader_state.event_state().contains(State::PRESENT))
         })
     }
 
-    #[napi]
+    /// get_status for the reader at `index` in the current reader list
+    #[napi(catch_unwind)]
+    pub fn get_status_by_index(&self, index: u32, timeout_ms: Option<u32>) -> Result<CardStatus> {
+        let reader_name = self.reader_at(index)?;
+        self.get_status(reader_name, timeout_ms)
+    }
+
+    /// connect to the reader at `index` in the current reader list (0 = first reader)
+    #[napi(catch_unwind)]
+    pub fn connect_by_index(&self, index: u32, share_mode: ShareMode, preferred_protocols: Option<Protocol>) -> Result<crate::card::Card> {
+        let reader_name = self.reader_at(index)?;
+        self.connect(reader_name, share_mode, preferred_protocols, None, None)
+    }
+
+    /// Wait until a card is present in the reader (returns at once if one already is)
+    #[napi(catch_unwind)]
     pub async fn wait_for_card(&self, reader_name: String, timeout_ms: u32) -> Result<CardStatus> {
-        let ctx = self.ctx.lock()
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to lock context: {}", e)))?;
-        
-        let mut buffer = vec![0u8; 1024];
-        let readers = ctx.list_readers(&mut buffer)
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to list readers: {}", e)))?;
-        
-        let reader_vec: Vec<_> = readers.collect();
-        let reader = reader_vec.iter()
-            .find(|r| r.to_string_lossy() == reader_name)
-            .ok_or_else(|| napi::Error::new(napi::Status::GenericFailure, format!("Reader not found: {}", reader_name)))?;
+        self.wait_for(reader_name, timeout_ms, |state| state.contains(State::PRESENT)).await
+    }
+
+    /// Wait until the card is removed from the reader (returns at once if the slot is already empty)
+    #[napi(catch_unwind)]
+    pub async fn wait_for_card_removal(&self, reader_name: String, timeout_ms: u32) -> Result<CardStatus> {
+        self.wait_for(reader_name, timeout_ms, |state| !state.contains(State::PRESENT)).await
+    }
+
+    /// Abort pending waits on this reader; they fail with CANCELLED (the monitor isn't affected)
+    #[napi(catch_unwind)]
+    pub fn cancel(&self) -> Result<()> {
+        for ctx in self.waits.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            ctx.cancel()
+                .map_err(|e| pcsc_error(CONTEXT_FAILED, "Failed to cancel", e))?;
+        }
+        Ok(())
+    }
+
+    /// Wait for a card event on any of `reader_names` with a single get_status_change
+    ///
+    /// With `include_pnp`, a reader being attached or detached is reported as a change of
+    /// the `\\?PnP?\Notification` pseudo-reader. Returns null if nothing changed within the timeout.
+    #[napi(catch_unwind)]
+    pub async fn wait_for_any_card(&self, reader_names: Vec<String>, timeout_ms: u32, include_pnp: Option<bool>) -> Result<Option<ReaderChange>> {
+        let mut reader_states = reader_names.iter()
+            .map(|name| {
+                CString::new(name.as_str())
+                    .map(|name| ReaderState::new(name, State::UNAWARE))
+                    .map_err(|e| error(INVALID_READER_NAME, format!("Failed to convert reader name: {}", e)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if include_pnp.unwrap_or(false) {
+            reader_states.push(ReaderState::new(pcsc::PNP_NOTIFICATION(), State::UNAWARE));
+        }
+        if reader_states.is_empty() {
+            return Err(error(INVALID_ARGUMENT, "No readers to wait on"));
+        }
         
+        let ctx = self.wait_context()?;
         let timeout = Duration::from_millis(timeout_ms as u64);
-        let reader_cstr = CString::new(reader.to_string_lossy().as_ref())
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to convert reader name: {}", e)))?;
-        let mut reader_states = vec![ReaderState::new(reader_cstr, State::UNAWARE)];
-        ctx.get_status_change(timeout, &mut reader_states)
-            .map_err(|e| napi::Error::new(napi::Status::GenericFailure, format!("Failed to get status change: {:?}", e)))?;
-        
-        let state = reader_states[0].event_state();
+        let worker_ctx = Context::clone(&ctx.ctx);
+        with_timeout(&ctx.ctx, move || {
+            // Learn the current states first so the blocking call only returns on a change
+            worker_ctx.get_status_change(Duration::from_secs(0), &mut reader_states)
+                .map_err(|e| pcsc_error(STATUS_FAILED, "Failed to get status", e))?;
+            for reader_state in reader_states.iter_mut() {
+                reader_state.sync_current_state();
+            }
+            
+            match worker_ctx.get_status_change(timeout, &mut reader_states) {
+                Ok(()) => {}
+                Err(pcsc::Error::Timeout) => return Ok(None),
+                Err(e) => return Err(pcsc_error(STATUS_FAILED, "Failed to get status change", e)),
+            }
+            Ok(reader_states.iter()
+                .find(|reader_state| reader_state.event_state().contains(State::CHANGED))
+                .map(|reader_state| ReaderChange {
+                    reader_name: reader_state.name().to_string_lossy().to_string(),
+                    status: reader_card_status(reader_state),
+                }))
+        }, timeout + WAIT_TIMEOUT_GRACE).await
+    }
+
+    /// Wait until a reader is attached or detached (PnP notification) and return the new reader list
+    ///
+    /// Returns null if the list didn't change within the timeout.
+    #[napi(catch_unwind)]
+    pub async fn wait_for_reader_change(&self, timeout_ms: u32) -> Result<Option<Vec<String>>> {
+        let ctx = self.wait_context()?;
+        let timeout = Duration::from_millis(timeout_ms as u64);
+        let worker_ctx = Context::clone(&ctx.ctx);
+        let readers = with_timeout(&ctx.ctx, move || {
+            let mut reader_states = vec![ReaderState::new(pcsc::PNP_NOTIFICATION(), State::UNAWARE)];
+            worker_ctx.get_status_change(Duration::from_secs(0), &mut reader_states)
+                .map_err(|e| pcsc_error(STATUS_FAILED, "Failed to get status", e))?;
+            reader_states[0].sync_current_state();
+            
+            match worker_ctx.get_status_change(timeout, &mut reader_states) {
+                Ok(()) => {}
+                Err(pcsc::Error::Timeout) => return Ok(None),
+                Err(e) => return Err(pcsc_error(STATUS_FAILED, "Failed to get status change", e)),
+            }
+            
+            reader_names(&worker_ctx).map(Some)
+        }, timeout + WAIT_TIMEOUT_GRACE).await?;
         
-        Ok(CardStatus {
-            present: state.contains(State::PRESENT),
-            empty: state.contains(State::EMPTY),
-            mute: state.contains(State::MUTE),
-            atr: None,
+        Ok(readers.map(|readers| {
+            let names = readers.iter().map(|r| r.to_string_lossy().to_string()).collect();
+            *self.readers.lock().unwrap_or_else(|e| e.into_inner()) = readers;
+            names
+        }))
+    }
+}
+
+impl SmartCardReader {
+    /// A clone of the shared context
+    ///
+    /// The lock is only held to take the clone, so a long get_status_change or connect on one
+    /// call doesn't hold up list_readers, cancel or other waits.
+    ///
+    /// A context invalidated by a restart of the smart card service is re-established first.
+    fn context(&self) -> Result<Context> {
+        let mut ctx = self.lock_context()?;
+        if ctx.is_valid().is_err() {
+            *ctx = establish(self.scope)?;
+        }
+        Ok(ctx.clone())
+    }
+
+    /// Run `op` on a clone of the shared context, retrying it once on a new context if it
+    /// fails because the smart card service went away
+    ///
+    /// pcsc-lite can keep reporting a context as valid after pcscd restarts; the operation
+    /// itself then fails with NO_SERVICE (SCARD_E_NO_SERVICE, SCARD_E_SERVICE_STOPPED) or
+    /// INVALID_HANDLE.
+    fn with_context<T>(&self, mut op: impl FnMut(&Context) -> Result<T>) -> Result<T> {
+        match op(&self.context()?) {
+            Err(e) if has_reason(&e, NO_SERVICE) || has_reason(&e, INVALID_HANDLE) => {
+                let ctx = establish(self.scope)?;
+                *self.lock_context()? = ctx.clone();
+                op(&ctx)
+            }
+            result => result,
+        }
+    }
+
+    /// Establish a context for one wait (see WaitContext)
+    fn wait_context(&self) -> Result<WaitContext<'_>> {
+        let ctx = Arc::new(establish(self.scope)?);
+        self.waits.lock().unwrap_or_else(|e| e.into_inner()).push(ctx.clone());
+        Ok(WaitContext { waits: &self.waits, ctx })
+    }
+
+    fn lock_context(&self) -> Result<MutexGuard<'_, Context>> {
+        self.ctx.lock()
+            .map_err(|e| error(LOCK_FAILED, format!("Failed to lock context: {}", e)))
+    }
+
+    /// List the readers and store the result in the cache
+    fn refresh_cache(&self, ctx: &Context) -> Result<Vec<CString>> {
+        let readers = reader_names(ctx)?;
+        *self.readers.lock().unwrap_or_else(|e| e.into_inner()) = readers.clone();
+        Ok(readers)
+    }
+
+    /// Look up a reader by name in the cache, re-listing the readers on a miss
+    fn find_reader(&self, ctx: &Context, reader_name: &str) -> Result<CString> {
+        let matches = |r: &CString| r.to_string_lossy() == reader_name;
+        if let Some(reader) = self.readers.lock().unwrap_or_else(|e| e.into_inner()).iter().find(|r| matches(r)) {
+            return Ok(reader.clone());
+        }
+        self.refresh_cache(ctx)?.into_iter()
+            .find(matches)
+            .ok_or_else(|| error(READER_NOT_FOUND, format!("Reader not found: {}", reader_name)))
+    }
+
+    /// Current state of every reader, from one listing and one zero-timeout get_status_change
+    fn all_reader_states(&self) -> Result<Vec<ReaderState>> {
+        self.with_context(|ctx| {
+            let mut reader_states: Vec<_> = self.refresh_cache(ctx)?.into_iter()
+                .map(|r| ReaderState::new(r, State::UNAWARE))
+                .collect();
+            if !reader_states.is_empty() {
+                ctx.get_status_change(Duration::from_secs(0), &mut reader_states)
+                    .map_err(|e| pcsc_error(STATUS_FAILED, "Failed to get status", e))?;
+            }
+            Ok(reader_states)
         })
     }
+
+    /// Name of the reader at `index` in a fresh listing
+    fn reader_at(&self, index: u32) -> Result<String> {
+        let readers = self.with_context(|ctx| self.refresh_cache(ctx))?;
+        readers.get(index as usize)
+            .map(|r| r.to_string_lossy().to_string())
+            .ok_or_else(|| error(READER_NOT_FOUND, format!("No reader at index {} ({} readers connected)", index, readers.len())))
+    }
+
+    /// Run wait_for_state on a worker thread without holding the context lock while blocked
+    async fn wait_for(&self, reader_name: String, timeout_ms: u32, done: fn(State) -> bool) -> Result<CardStatus> {
+        let ctx = self.wait_context()?;
+        let reader_cstr = self.find_reader(&ctx.ctx, &reader_name)?;
+        
+        let timeout = Duration::from_millis(timeout_ms as u64);
+        let worker_ctx = Context::clone(&ctx.ctx);
+        with_timeout(&ctx.ctx, move || {
+            wait_for_state(&worker_ctx, reader_cstr, timeout, done)
+        }, timeout + WAIT_TIMEOUT_GRACE).await
+    }
+}
+
+/// A context of its own for one timed wait, so the cancel on timeout doesn't abort the
+/// reader's other waits; registered with the reader while it lives so cancel reaches it
+struct WaitContext<'a> {
+    waits: &'a Mutex<Vec<Arc<Context>>>,
+    ctx: Arc<Context>,
+}
+
+impl Drop for WaitContext<'_> {
+    fn drop(&mut self) {
+        self.waits.lock().unwrap_or_else(|e| e.into_inner()).retain(|ctx| !Arc::ptr_eq(ctx, &self.ctx));
+    }
+}
+
+fn establish(scope: Scope) -> Result<Context> {
+    Context::establish(scope)
+        .map_err(|e| pcsc_error(CONTEXT_FAILED, "Failed to establish PC/SC context", e))
+}
+
+/// All reader names, with the buffer sized by a length probe
+fn reader_names(ctx: &Context) -> Result<Vec<CString>> {
+    probed_list(
+        || ctx.list_readers_len(),
+        |buffer| ctx.list_readers(buffer).map(|readers| readers.map(CStr::to_owned).collect()),
+    )
+}
+
+/// Read a PC/SC name list into a buffer sized by `probe`
+///
+/// A reader plugged in between the probe and the read makes the buffer too small, so the
+/// probe is repeated a few times before giving up.
+pub(crate) fn probed_list(
+    mut probe: impl FnMut() -> pcsc::Result<usize>,
+    mut read: impl FnMut(&mut [u8]) -> pcsc::Result<Vec<CString>>,
+) -> Result<Vec<CString>> {
+    for _ in 0..LIST_READERS_ATTEMPTS {
+        let len = match probe() {
+            Ok(len) => len,
+            Err(pcsc::Error::NoReadersAvailable) => return Ok(Vec::new()),
+            Err(e) => return Err(pcsc_error(LIST_READERS_FAILED, "Failed to list readers", e)),
+        };
+        let mut buffer = vec![0u8; len];
+        match read(&mut buffer) {
+            Ok(names) => return Ok(names),
+            Err(pcsc::Error::InsufficientBuffer) => continue,
+            Err(e) => return Err(pcsc_error(LIST_READERS_FAILED, "Failed to list readers", e)),
+        }
+    }
+    Err(error(LIST_READERS_FAILED, "Failed to list readers: the reader list kept changing"))
+}
+
+/// Map the numeric scope used by the JS API (0 = User, 1 = System, 2 = Terminal)
+fn to_scope(scope: u32) -> Scope {
+    match scope {
+        1 => Scope::System,
+        2 => Scope::Terminal,
+        _ => Scope::User,
+    }
+}
+
+/// Block in get_status_change until `done(event_state)` holds or `timeout` elapses
+///
+/// The first call starts from UNAWARE and returns the current state straight away; after
+/// that each call waits for a change from the last seen state. Running out of time isn't
+/// an error: the last observed status is returned.
+fn wait_for_state(ctx: &Context, reader: CString, timeout: Duration, done: impl Fn(State) -> bool) -> Result<CardStatus> {
+    let deadline = Instant::now() + timeout;
+    let mut reader_states = vec![ReaderState::new(reader, State::UNAWARE)];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match ctx.get_status_change(remaining, &mut reader_states) {
+            Ok(()) => {}
+            Err(pcsc::Error::Timeout) => break,
+            Err(e) => return Err(pcsc_error(STATUS_FAILED, "Failed to get status change", e)),
+        }
+        if done(reader_states[0].event_state()) || remaining.is_zero() {
+            break;
+        }
+        reader_states[0].sync_current_state();
+    }
+    Ok(reader_card_status(&reader_states[0]))
 }
 
+/// Card status from a reader state filled in by get_status_change
+///
+/// The ATR is only reported for a present, responsive card.
+pub(crate) fn reader_card_status(reader_state: &ReaderState) -> CardStatus {
+    let state = reader_state.event_state();
+    let present = state.contains(State::PRESENT);
+    let mute = state.contains(State::MUTE);
+    CardStatus {
+        present,
+        empty: state.contains(State::EMPTY),
+        mute,
+        atr: if present && !mute && !reader_state.atr().is_empty() {
+            Some(Buffer::from(reader_state.atr().to_vec()))
+        } else {
+            None
+        },
+        change_count: Some(reader_state.event_count()),
+        in_use: Some(state.contains(State::INUSE)),
+        exclusive: Some(state.contains(State::EXCLUSIVE)),
+        unpowered: Some(state.contains(State::UNPOWERED)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::groups::parse_multi_string;
+    use std::cell::{Cell, RefCell};
+
+    /// A reader list as SCardListReaders returns it: NUL-separated, ending in an empty name
+    fn multi_string(names: &[String]) -> Vec<u8> {
+        let mut list: Vec<u8> = names.iter().flat_map(|name| name.bytes().chain([0])).collect();
+        list.push(0);
+        list
+    }
+
+    /// SCardListReaders over `list`: InsufficientBuffer when the buffer can't hold it
+    fn read_into(list: &[u8], buffer: &mut [u8]) -> pcsc::Result<Vec<CString>> {
+        let target = buffer.get_mut(..list.len()).ok_or(pcsc::Error::InsufficientBuffer)?;
+        target.copy_from_slice(list);
+        Ok(parse_multi_string(target))
+    }
+
+    fn reader(index: usize) -> String {
+        format!("ACS ACR39U ICC Reader {:02} 00", index)
+    }
+
+    #[test]
+    fn long_reader_list_is_read_in_full() {
+        let names: Vec<String> = (0..64).map(reader).collect();
+        let list = multi_string(&names);
+        let readers = probed_list(|| Ok(list.len()), |buffer| read_into(&list, buffer)).unwrap();
+        let readers: Vec<String> = readers.iter().map(|r| r.to_string_lossy().to_string()).collect();
+        assert_eq!(readers, names);
+    }
+
+    #[test]
+    fn reader_plugged_in_after_the_probe_is_picked_up_on_the_next_probe() {
+        let list = RefCell::new(multi_string(&[reader(0)]));
+        let probes = Cell::new(0);
+        let readers = probed_list(
+            || {
+                probes.set(probes.get() + 1);
+                Ok(list.borrow().len())
+            },
+            |buffer| {
+                if probes.get() == 1 {
+                    *list.borrow_mut() = multi_string(&[reader(0), reader(1)]);
+                }
+                read_into(&list.borrow(), buffer)
+            },
+        ).unwrap();
+        assert_eq!(probes.get(), 2);
+        assert_eq!(readers.len(), 2);
+    }
+
+    #[test]
+    fn reader_list_that_keeps_growing_fails() {
+        let probes = Cell::new(0);
+        let Err(e) = probed_list(
+            || {
+                probes.set(probes.get() + 1);
+                Ok(probes.get())
+            },
+            |_| Err(pcsc::Error::InsufficientBuffer),
+        ) else {
+            panic!("expected LIST_READERS_FAILED");
+        };
+        assert!(has_reason(&e, LIST_READERS_FAILED));
+        assert_eq!(probes.get(), LIST_READERS_ATTEMPTS as usize);
+    }
+
+    #[test]
+    fn no_readers_is_an_empty_list() {
+        let readers = probed_list(|| Err(pcsc::Error::NoReadersAvailable), |_| unreachable!()).unwrap();
+        assert!(readers.is_empty());
+    }
+
+    /// Needs a running PC/SC service and a reader with no card in it
+    #[test]
+    #[ignore]
+    fn list_readers_is_not_blocked_by_wait_for_card() {
+        let reader = SmartCardReader::new(None, None).unwrap();
+        let name = reader.list_readers(None).unwrap().into_iter().next().expect("no reader connected");
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        std::thread::scope(|scope| {
+            let wait = scope.spawn(|| runtime.block_on(reader.wait_for_card(name.clone(), 2000)));
+            std::thread::sleep(Duration::from_millis(200));
+            assert!(!wait.is_finished(), "the reader must be empty for this test");
+            
+            let started = Instant::now();
+            reader.list_readers(None).unwrap();
+            assert!(started.elapsed() < Duration::from_millis(500));
+        });
+    }
+}
@@ -0,0 +1,345 @@
+// Thai national ID card (MOI applet) helpers
+
+use crate::card::{Card, SELECT_RESPONSE_LENGTH};
+use crate::error::{error, COMMAND_FAILED, INVALID_ARGUMENT, NOT_THAI_ID};
+use crate::logger;
+use crate::types::{SignedBlock, SignedBlockLayout, ThaiIdAddress, ThaiIdCard, ThaiIdData, TransmitResult};
+use crate::utils::{base64, thai_date_to_iso, thai_id_generation, thai_id_get_response_p2, tis620_to_string};
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::JsFunction;
+use napi_derive::napi;
+use serde::Serialize;
+
+/// SELECT of the MOI applet (AID A0 00 00 00 54 48 00 01)
+const SELECT_MOI: [u8; 13] = [0x00, 0xA4, 0x04, 0x00, 0x08, 0xA0, 0x00, 0x00, 0x00, 0x54, 0x48, 0x00, 0x01];
+
+// Field locations (offset, length) in the MOI applet
+const CID: (u32, u32) = (0x0004, 13);
+const THAI_NAME: (u32, u32) = (0x0011, 100);
+const ENGLISH_NAME: (u32, u32) = (0x0075, 100);
+const BIRTH_DATE: (u32, u32) = (0x00D9, 8);
+const ADDRESS: (u32, u32) = (0x1579, 100);
+const ISSUE_DATE: (u32, u32) = (0x0167, 8);
+const EXPIRE_DATE: (u32, u32) = (0x016F, 8);
+
+/// The photo (JPEG) is stored in 20 blocks of 0xFF bytes from 0x017B
+const PHOTO_OFFSET: u32 = 0x017B;
+const PHOTO_BLOCK_SIZE: u32 = 0xFF;
+const PHOTO_BLOCKS: u32 = 20;
+
+impl Card {
+    /// exchange with the GET RESPONSE this chip generation expects (detected from the ATR)
+    fn moi_exchange(&self, card: Option<&pcsc::Card>, command: &[u8], response_length: u32) -> Result<TransmitResult> {
+        let generation = self.atr.clone().and_then(thai_id_generation);
+        self.exchange_with_get_response_p2(card, command, response_length, 3, thai_id_get_response_p2(generation))
+    }
+
+    /// Select the MOI applet, failing with NOT_THAI_ID on other cards
+    pub(crate) fn select_moi(&self, card: Option<&pcsc::Card>) -> Result<()> {
+        let result = self.moi_exchange(card, &SELECT_MOI, SELECT_RESPONSE_LENGTH)?;
+        if !is_ok(result.sw1, result.sw2) {
+            return Err(error(NOT_THAI_ID, format!("Not a Thai national ID card (SELECT returned {:02X}{:02X})", result.sw1, result.sw2)));
+        }
+        Ok(())
+    }
+
+    /// Read `length` bytes at `offset` with the MOI READ BINARY (80 B0 <offset> 02 00 <len>)
+    pub(crate) fn read_moi(&self, card: Option<&pcsc::Card>, offset: u32, length: u32) -> Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(length as usize);
+        let end = offset + length;
+        let mut position = offset;
+        while position < end {
+            let chunk = (end - position).min(0xFF);
+            let command = [0x80, 0xB0, (position >> 8) as u8, position as u8, 0x02, 0x00, chunk as u8];
+            let result = self.moi_exchange(card, &command, chunk)?;
+            if !is_ok(result.sw1, result.sw2) {
+                return Err(error(COMMAND_FAILED, format!("READ BINARY at offset {:04X} failed with status {:02X}{:02X}", position, result.sw1, result.sw2)));
+            }
+            data.extend_from_slice(&result.data);
+            position += chunk;
+        }
+        Ok(data)
+    }
+
+    /// Read a text field and decode it (TIS-620), trimming the space padding
+    fn read_moi_text(&self, card: Option<&pcsc::Card>, (offset, length): (u32, u32)) -> Result<String> {
+        let bytes = self.read_moi(card, offset, length)?;
+        Ok(tis620_to_string(&bytes).trim().to_string())
+    }
+
+    /// Read a date field that older cards may lack: None when the card refuses the read or the
+    /// field is blank
+    fn read_moi_date(&self, card: Option<&pcsc::Card>, (offset, length): (u32, u32)) -> Result<Option<String>> {
+        let command = [0x80, 0xB0, (offset >> 8) as u8, offset as u8, 0x02, 0x00, length as u8];
+        let result = self.moi_exchange(card, &command, length)?;
+        if !is_ok(result.sw1, result.sw2) {
+            return Ok(None);
+        }
+        let date = tis620_to_string(&result.data).trim().to_string();
+        Ok((!date.is_empty() && date.chars().any(|c| c != '0')).then_some(date))
+    }
+
+    /// Read the photo blocks after select_moi, stopping early at the first block the card refuses
+    fn read_photo(&self, card: Option<&pcsc::Card>, mut on_block: impl FnMut(u32) -> Result<()>) -> Result<Vec<u8>> {
+        let mut photo = Vec::with_capacity((PHOTO_BLOCKS * PHOTO_BLOCK_SIZE) as usize);
+        for block in 0..PHOTO_BLOCKS {
+            let offset = PHOTO_OFFSET + block * PHOTO_BLOCK_SIZE;
+            let command = [0x80, 0xB0, (offset >> 8) as u8, offset as u8, 0x02, 0x00, PHOTO_BLOCK_SIZE as u8];
+            let result = self.moi_exchange(card, &command, PHOTO_BLOCK_SIZE)?;
+            if !is_ok(result.sw1, result.sw2) {
+                if block == 0 {
+                    return Err(error(COMMAND_FAILED, format!("Photo read failed with status {:02X}{:02X}", result.sw1, result.sw2)));
+                }
+                logger::log(format!("Photo read stopped after {} of {} blocks ({:02X}{:02X})", block, PHOTO_BLOCKS, result.sw1, result.sw2));
+                break;
+            }
+            photo.extend_from_slice(&result.data);
+            on_block(block + 1)?;
+        }
+        Ok(photo)
+    }
+
+    /// Personal data fields, after select_moi
+    fn read_id_data(&self, card: Option<&pcsc::Card>) -> Result<ThaiIdData> {
+        Ok(ThaiIdData {
+            cid: self.read_moi_text(card, CID)?,
+            thai_name: self.read_moi_text(card, THAI_NAME)?,
+            english_name: self.read_moi_text(card, ENGLISH_NAME)?,
+            birth_date: self.read_moi_text(card, BIRTH_DATE)?,
+            issue_date: self.read_moi_date(card, ISSUE_DATE)?,
+            expire_date: self.read_moi_date(card, EXPIRE_DATE)?,
+        })
+    }
+
+    /// The address split into its parts, after select_moi
+    fn read_address(&self, card: Option<&pcsc::Card>) -> Result<ThaiIdAddress> {
+        let address = self.read_moi_text(card, ADDRESS)?;
+        let mut parts = address.split('#').map(|part| part.trim().to_string());
+        let mut next = || parts.next().unwrap_or_default();
+        Ok(ThaiIdAddress {
+            house_no: next(),
+            moo: next(),
+            trok: next(),
+            soi: next(),
+            road: next(),
+            tambon: next(),
+            amphoe: next(),
+            changwat: next(),
+        })
+    }
+}
+
+/// Call a JS progress callback with (blocks_read, total_blocks)
+fn report_progress(env: &Env, on_progress: Option<&JsFunction>, blocks_read: u32) -> Result<()> {
+    if let Some(callback) = on_progress {
+        callback.call(None, &[env.create_uint32(blocks_read)?, env.create_uint32(PHOTO_BLOCKS)?])?;
+    }
+    Ok(())
+}
+
+#[napi]
+impl Card {
+    /// Whether the ATR captured at connect is that of a Thai ID chip generation (no APDU is sent)
+    #[napi(catch_unwind)]
+    pub fn is_thai_national_id(&self) -> bool {
+        self.atr.clone().is_some_and(|atr| thai_id_generation(atr).is_some())
+    }
+
+    /// Read the citizen ID, Thai and English names, date of birth and issue/expiry dates
+    ///
+    /// Fails with NOT_THAI_ID if the MOI applet can't be selected. Names keep the card's
+    /// `#` separators (title#first#middle#last); dates are YYYYMMDD in the Buddhist era.
+    #[napi(catch_unwind)]
+    pub fn read_thai_id(&self, disconnect_after: Option<u32>) -> Result<ThaiIdData> {
+        self.read_then_release(disconnect_after, |card| {
+            self.select_moi(card)?;
+            self.read_id_data(card)
+        })
+    }
+
+    /// Read the registered address, split into its `#`-separated parts
+    ///
+    /// Parts keep the card's own prefixes (e.g. หมู่ที่, ตำบล); absent parts are empty strings.
+    #[napi(catch_unwind)]
+    pub fn read_thai_id_address(&self, disconnect_after: Option<u32>) -> Result<ThaiIdAddress> {
+        self.read_then_release(disconnect_after, |card| {
+            self.select_moi(card)?;
+            self.read_address(card)
+        })
+    }
+
+    /// Read the photo (JPEG) block by block, stopping early at the first block the card refuses
+    ///
+    /// `on_progress(blocks_read, total_blocks)` is called after each block.
+    #[napi(catch_unwind, ts_args_type = "disconnectAfter?: number, onProgress?: (blocksRead: number, totalBlocks: number) => void")]
+    pub fn read_thai_id_photo(&self, env: Env, disconnect_after: Option<u32>, on_progress: Option<JsFunction>) -> Result<Buffer> {
+        self.read_then_release(disconnect_after, |card| {
+            self.select_moi(card)?;
+            Ok(Buffer::from(self.read_photo(card, |blocks_read| report_progress(&env, on_progress.as_ref(), blocks_read))?))
+        })
+    }
+
+    /// Like read_thai_id_photo, but returns a `data:image/jpeg;base64,...` URI for an `<img src>`
+    #[napi(catch_unwind, ts_args_type = "disconnectAfter?: number, onProgress?: (blocksRead: number, totalBlocks: number) => void")]
+    pub fn read_thai_id_photo_data_uri(&self, env: Env, disconnect_after: Option<u32>, on_progress: Option<JsFunction>) -> Result<String> {
+        self.read_then_release(disconnect_after, |card| {
+            self.select_moi(card)?;
+            let photo = self.read_photo(card, |blocks_read| report_progress(&env, on_progress.as_ref(), blocks_read))?;
+            Ok(format!("data:image/jpeg;base64,{}", base64(&photo)))
+        })
+    }
+
+    /// Read the personal data, address and photo on a worker thread, in one transaction
+    ///
+    /// `on_progress(blocks_read, total_blocks)` is called from the event loop as photo blocks
+    /// arrive. Fails as a whole (e.g. with CARD_REMOVED) if any part can't be read.
+    #[napi(catch_unwind, ts_args_type = "disconnectAfter?: number, onProgress?: (blocksRead: number, totalBlocks: number) => void", ts_return_type = "Promise<ThaiIdCard>")]
+    pub fn read_thai_id_all_async(&self, disconnect_after: Option<u32>, on_progress: Option<JsFunction>) -> Result<AsyncTask<ThaiIdReadTask>> {
+        let progress = on_progress
+            .map(|callback| callback.create_threadsafe_function(0, |ctx: ThreadSafeCallContext<u32>| {
+                Ok(vec![ctx.env.create_uint32(ctx.value)?, ctx.env.create_uint32(PHOTO_BLOCKS)?])
+            }))
+            .transpose()?;
+        
+        Ok(AsyncTask::new(ThaiIdReadTask {
+            card: self.worker(),
+            disconnect_after,
+            progress,
+        }))
+    }
+
+    /// Read a signed data block, its signature and (optionally) the signer certificate
+    ///
+    /// Where the signature lives differs between chip generations and isn't published,
+    /// so the caller supplies the layout. Verification is left to the caller.
+    #[napi(catch_unwind)]
+    pub fn read_thai_signed_data(&self, layout: SignedBlockLayout, disconnect_after: Option<u32>) -> Result<SignedBlock> {
+        self.read_then_release(disconnect_after, |card| {
+            self.select_moi(card)?;
+            
+            let data = self.read_moi(card, layout.data_offset, layout.data_length)?;
+            let signature = self.read_moi(card, layout.signature_offset, layout.signature_length)?;
+            let cert = match (layout.cert_offset, layout.cert_length) {
+                (Some(offset), Some(length)) => Some(Buffer::from(self.read_moi(card, offset, length)?)),
+                _ => None,
+            };
+            
+            Ok(SignedBlock {
+                data: Buffer::from(data),
+                signature: Buffer::from(signature),
+                cert,
+            })
+        })
+    }
+}
+
+/// Worker-thread half of read_thai_id_all_async
+pub struct ThaiIdReadTask {
+    card: Card,
+    disconnect_after: Option<u32>,
+    progress: Option<ThreadsafeFunction<u32, ErrorStrategy::Fatal>>,
+}
+
+impl Task for ThaiIdReadTask {
+    type Output = ThaiIdCard;
+    type JsValue = ThaiIdCard;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let card = &self.card;
+        let progress = &self.progress;
+        card.read_then_release(self.disconnect_after, |handle| {
+            card.select_moi(handle)?;
+            
+            let data = card.read_id_data(handle)?;
+            let address = card.read_address(handle)?;
+            let photo = card.read_photo(handle, |blocks_read| {
+                if let Some(progress) = progress {
+                    progress.call(blocks_read, ThreadsafeFunctionCallMode::NonBlocking);
+                }
+                Ok(())
+            })?;
+            Ok(ThaiIdCard { data, address, photo: Buffer::from(photo) })
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Shape of thai_id_to_json; field names are snake_case and only ever added to
+#[derive(Serialize)]
+struct ThaiIdJson<'a> {
+    cid: &'a str,
+    name_th: PersonName<'a>,
+    name_en: PersonName<'a>,
+    /// ISO dates, null when the card marks them unknown
+    birth_date: Option<String>,
+    issue_date: Option<String>,
+    expire_date: Option<String>,
+    address: &'a ThaiIdAddress,
+    /// JPEG, base64
+    photo: String,
+}
+
+#[derive(Serialize)]
+struct PersonName<'a> {
+    title: &'a str,
+    first: &'a str,
+    middle: &'a str,
+    last: &'a str,
+}
+
+impl<'a> PersonName<'a> {
+    /// Split a `title#first#middle#last` name
+    fn parse(name: &'a str) -> Self {
+        let mut parts = name.split('#').map(str::trim);
+        let mut next = || parts.next().unwrap_or_default();
+        PersonName { title: next(), first: next(), middle: next(), last: next() }
+    }
+}
+
+/// Serialize a read_thai_id_all_async result as JSON, for audit logs and API responses
+///
+/// Names are split into title/first/middle/last, dates converted to ISO (null when unknown)
+/// and the photo base64-encoded. Field names are snake_case.
+#[napi(catch_unwind)]
+pub fn thai_id_to_json(card: ThaiIdCard, pretty: Option<bool>) -> Result<String> {
+    let date = |be_date: &str| thai_date_to_iso(be_date.to_string());
+    let json = ThaiIdJson {
+        cid: &card.data.cid,
+        name_th: PersonName::parse(&card.data.thai_name),
+        name_en: PersonName::parse(&card.data.english_name),
+        birth_date: date(&card.data.birth_date),
+        issue_date: card.data.issue_date.as_deref().and_then(date),
+        expire_date: card.data.expire_date.as_deref().and_then(date),
+        address: &card.address,
+        photo: base64(&card.photo),
+    };
+    let encoded = if pretty.unwrap_or(false) {
+        serde_json::to_string_pretty(&json)
+    } else {
+        serde_json::to_string(&json)
+    };
+    encoded.map_err(|e| error(INVALID_ARGUMENT, format!("Failed to serialize Thai ID data: {}", e)))
+}
+
+/// 90 00, or 61 XX when the GET RESPONSE chain already collected the data
+fn is_ok(sw1: u8, sw2: u8) -> bool {
+    (sw1 == 0x90 && sw2 == 0x00) || sw1 == 0x61
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_thai_national_id_accepts_unlisted_newer_chips() {
+        let mut card = Card::dry_run();
+        card.atr = Some(Buffer::from(vec![0x3B, 0x67, 0x00, 0x00, 0xA6, 0x40, 0x40, 0x00, 0x09, 0x90, 0x00]));
+        assert!(card.is_thai_national_id());
+        card.atr = Some(Buffer::from(vec![0x3B, 0x8F, 0x80, 0x01, 0x80, 0x4F]));
+        assert!(!card.is_thai_national_id());
+    }
+}
@@ -0,0 +1,41 @@
+use napi::Result;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Spaces out operations that follow a failure, so retries can't hammer a broken reader
+#[derive(Default)]
+pub(crate) struct Throttle {
+    state: Mutex<ThrottleState>,
+}
+
+#[derive(Default)]
+struct ThrottleState {
+    min_interval: Duration,
+    last_failure: Option<Instant>,
+}
+
+impl Throttle {
+    pub(crate) fn set_min_interval(&self, min_interval: Duration) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).min_interval = min_interval;
+    }
+
+    /// Sleep until the minimum interval since the last failure has passed
+    pub(crate) fn wait(&self) {
+        let delay = {
+            let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            match state.last_failure {
+                Some(at) => state.min_interval.saturating_sub(at.elapsed()),
+                None => Duration::ZERO,
+            }
+        };
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+    }
+
+    /// Remember whether the last operation failed
+    pub(crate) fn record<T>(&self, result: &Result<T>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.last_failure = if result.is_err() { Some(Instant::now()) } else { None };
+    }
+}
@@ -0,0 +1,52 @@
+use crate::error::{error, TASK_FAILED, TIMEOUT};
+use napi::Result;
+use pcsc::Context;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Run a blocking PC/SC operation on a worker thread, racing it against `timeout`
+///
+/// On timeout `ctx` is cancelled so the blocked call returns instead of leaking the worker,
+/// and a TIMEOUT error is returned. That aborts every call on the context, so `ctx` should be
+/// one the operation has to itself.
+pub(crate) async fn with_timeout<T, F>(ctx: &Context, op: F, timeout: Duration) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let task = tokio::task::spawn_blocking(op);
+    match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err(error(TASK_FAILED, format!("Worker task failed: {}", e))),
+        Err(_) => {
+            let _ = ctx.cancel();
+            Err(error(TIMEOUT, format!("Operation timed out after {} ms", timeout.as_millis())))
+        }
+    }
+}
+
+/// Run a blocking operation on its own thread, giving up after `timeout` with a TIMEOUT error
+///
+/// PC/SC offers no way to abort a blocked SCardTransmit, so on timeout the thread is left
+/// to finish (and drop its result) in the background.
+pub(crate) fn with_thread_timeout<T, F>(op: F, timeout: Duration) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .spawn(move || {
+            let _ = sender.send(op());
+        })
+        .map_err(|e| error(TASK_FAILED, format!("Failed to start worker thread: {}", e)))?;
+    
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            Err(error(TIMEOUT, format!("Operation timed out after {} ms", timeout.as_millis())))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(error(TASK_FAILED, "Worker thread panicked")),
+    }
+}
@@ -1,12 +1,38 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
+use serde::Serialize;
+
+/// How the card is shared with other applications
+#[napi]
+pub enum ShareMode {
+    Shared = 0,
+    Exclusive = 1,
+    /// Talk to the reader itself, even with no card in it
+    Direct = 2,
+}
+
+/// Card protocol (Any lets the reader negotiate)
+#[napi]
+pub enum Protocol {
+    T0 = 0,
+    T1 = 1,
+    Raw = 2,
+    Any = 3,
+}
 
 /// Result of APDU transmission
 #[napi(object)]
 pub struct TransmitResult {
     pub data: Buffer,
+    /// Status word of the last exchange (after any GET RESPONSE chain)
     pub sw1: u8,
     pub sw2: u8,
+    /// SW1 and SW2 combined (e.g. 0x9000)
+    pub status_word: u16,
+    /// The status word is 90 00
+    pub is_success: bool,
+    /// Number of GET RESPONSE commands issued (only set when chaining happened)
+    pub get_response_iterations: Option<u32>,
 }
 
 /// Card status information
@@ -16,5 +42,194 @@ pub struct CardStatus {
     pub empty: bool,
     pub mute: bool,
     pub atr: Option<Buffer>,
+    /// Reader event counter, bumped on every card insertion and removal (reader status only)
+    ///
+    /// A different count with `present` still true means the card was swapped between polls.
+    pub change_count: Option<u32>,
+    /// Some application, possibly this one, is connected to the card (reader status only)
+    pub in_use: Option<bool>,
+    /// An application holds the card in exclusive mode, so connects fail (reader status only)
+    pub exclusive: Option<bool>,
+    /// The card is present but not powered (reader status only)
+    pub unpowered: Option<bool>,
+}
+
+/// Everything SCardStatus reports for a connected card
+#[napi(object)]
+pub struct FullCardStatus {
+    /// Names of the reader the connection is on (more than one for a reader with aliases)
+    pub reader_names: Vec<String>,
+    /// No card in the reader
+    pub absent: bool,
+    /// A card is in the reader
+    pub present: bool,
+    /// The card is in position for use
+    pub swallowed: bool,
+    /// The card is powered
+    pub powered: bool,
+    /// Card was reset and awaits protocol negotiation
+    pub negotiable: bool,
+    /// A protocol has been set
+    pub specific: bool,
+    /// Active protocol (unset for a direct connection)
+    pub protocol: Option<Protocol>,
+    pub atr: Option<Buffer>,
+}
+
+/// Reader name with card presence
+#[napi(object)]
+pub struct ReaderDetail {
+    pub name: String,
+    pub present: bool,
+    pub atr: Option<Buffer>,
+}
+
+/// Reader name with its full card status
+#[napi(object)]
+pub struct ReaderWithStatus {
+    pub name: String,
+    pub status: CardStatus,
+}
+
+/// A PC/SC part 10 reader feature and the control code that invokes it
+#[napi(object)]
+pub struct ReaderFeature {
+    /// Feature tag, e.g. 0x06 = FEATURE_VERIFY_PIN_DIRECT, 0x07 = FEATURE_MODIFY_PIN_DIRECT
+    pub tag: u8,
+    /// Control code to pass to control()
+    pub control_code: u32,
+}
+
+/// A reader whose state changed, as reported by wait_for_any_card
+#[napi(object)]
+pub struct ReaderChange {
+    pub reader_name: String,
+    pub status: CardStatus,
+}
+
+/// Protocol parameters negotiated at connect
+#[napi(object)]
+pub struct ProtocolParams {
+    /// Active protocol (unset for a direct connection)
+    pub protocol: Option<Protocol>,
+    /// Clock rate conversion index (FI) from TA1
+    pub fi: u8,
+    /// Baud rate adjustment index (DI) from TA1
+    pub di: u8,
+    /// Current F value reported by the reader, if supported
+    pub current_f: Option<u32>,
+    /// Current D value reported by the reader, if supported
+    pub current_d: Option<u32>,
+}
+
+/// Contents of an EF with the size declared in its FCP
+#[napi(object)]
+pub struct FileContents {
+    pub data: Buffer,
+    /// File size from the FCP, if the card reported one
+    pub declared_size: Option<u32>,
+    /// False when fewer bytes were read than the FCP declared
+    pub complete: bool,
+}
+
+/// Location of a signed data block and its signature on a Thai ID card
+#[napi(object)]
+pub struct SignedBlockLayout {
+    pub data_offset: u32,
+    pub data_length: u32,
+    pub signature_offset: u32,
+    pub signature_length: u32,
+    pub cert_offset: Option<u32>,
+    pub cert_length: Option<u32>,
+}
+
+/// Raw signed data read from the card, for offline verification
+#[napi(object)]
+pub struct SignedBlock {
+    pub data: Buffer,
+    pub signature: Buffer,
+    pub cert: Option<Buffer>,
 }
 
+/// Personal data read from a Thai national ID card
+#[napi(object)]
+pub struct ThaiIdData {
+    /// 13-digit citizen ID
+    pub cid: String,
+    /// Thai name, `#`-separated (title#first#middle#last)
+    pub thai_name: String,
+    /// English name, `#`-separated (title#first#middle#last)
+    pub english_name: String,
+    /// Date of birth, YYYYMMDD in the Buddhist era
+    pub birth_date: String,
+    /// Issue date, YYYYMMDD in the Buddhist era (unset on cards without it)
+    pub issue_date: Option<String>,
+    /// Expiry date, YYYYMMDD in the Buddhist era (unset on cards without it)
+    pub expire_date: Option<String>,
+}
+
+/// Registered address on a Thai national ID card
+#[napi(object)]
+#[derive(Serialize)]
+pub struct ThaiIdAddress {
+    pub house_no: String,
+    /// Village number (หมู่ที่)
+    pub moo: String,
+    /// Lane (ตรอก)
+    pub trok: String,
+    /// Alley (ซอย)
+    pub soi: String,
+    pub road: String,
+    /// Sub-district (ตำบล/แขวง)
+    pub tambon: String,
+    /// District (อำเภอ/เขต)
+    pub amphoe: String,
+    /// Province (จังหวัด)
+    pub changwat: String,
+}
+
+/// Everything read_thai_id_all_async reads from a Thai national ID card
+#[napi(object)]
+pub struct ThaiIdCard {
+    pub data: ThaiIdData,
+    pub address: ThaiIdAddress,
+    /// JPEG photo
+    pub photo: Buffer,
+}
+
+/// ATR broken into its parts
+#[napi(object)]
+pub struct AtrInfo {
+    /// "direct" (TS = 3B) or "inverse" (TS = 3F)
+    pub convention: String,
+    /// Format byte: interface byte indicators and historical byte count
+    pub t0: u8,
+    /// Protocols indicated by the TDi bytes (T=0 when none are given)
+    pub protocols: Vec<u32>,
+    pub historical_bytes: Buffer,
+    /// Thai ID chip generation, if the ATR is a known one
+    pub thai_id_generation: Option<u8>,
+}
+
+/// Outcome of a PIN VERIFY
+#[napi(object)]
+pub struct VerifyResult {
+    pub verified: bool,
+    /// The PIN is blocked (6983, or 63C0 after the last failed try)
+    pub blocked: bool,
+    /// Tries left, from 63Cx (0 when blocked); not every card reports it
+    pub tries_remaining: Option<u32>,
+    pub status_word: u16,
+}
+
+/// Versions and platform the library was built for
+#[napi(object)]
+pub struct EnvironmentInfo {
+    pub crate_version: String,
+    /// Minimum pcsc crate version required (the build may use a newer 2.x)
+    pub pcsc_version: String,
+    /// PC/SC implementation linked against (pcsc-lite, WinSCard or PCSC.framework)
+    pub pcsc_backend: String,
+    /// `<os>-<arch>`, e.g. linux-x86_64
+    pub platform: String,
+}
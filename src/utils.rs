@@ -1,8 +1,485 @@
+use crate::error::{error, INVALID_ARGUMENT};
+use crate::types::{AtrInfo, EnvironmentInfo};
+use napi::bindgen_prelude::*;
 use napi_derive::napi;
 
+/// Minimum pcsc crate version, as required in Cargo.toml (the build may resolve a newer 2.x)
+const PCSC_CRATE_VERSION: &str = "2.9";
+
 /// Get library version
-#[napi]
+#[napi(catch_unwind)]
 pub fn get_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
+/// Library, pcsc crate and platform details, for bug reports
+#[napi(catch_unwind)]
+pub fn get_environment_info() -> EnvironmentInfo {
+    let pcsc_backend = if cfg!(windows) {
+        "WinSCard"
+    } else if cfg!(target_os = "macos") {
+        "PCSC.framework"
+    } else {
+        "pcsc-lite"
+    };
+    
+    EnvironmentInfo {
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        pcsc_version: PCSC_CRATE_VERSION.to_string(),
+        pcsc_backend: pcsc_backend.to_string(),
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+    }
+}
+
+/// Platform IOCTL value for a reader control function (SCARD_CTL_CODE)
+#[napi(catch_unwind)]
+pub fn ctl_code(code: u32) -> u32 {
+    pcsc::ctl_code(code)
+}
+
+/// Build a command APDU, choosing case 1-4 and short or extended length fields
+///
+/// `le` is the expected response length (256 and 0 both encode as a short Le of 00;
+/// above 256 an extended Le is used).
+#[napi(catch_unwind)]
+pub fn build_apdu(cla: u8, ins: u8, p1: u8, p2: u8, data: Option<Buffer>, le: Option<u32>) -> Result<Buffer> {
+    let data = data.as_deref().unwrap_or(&[]);
+    if data.len() > 0xFFFF {
+        return Err(error(INVALID_ARGUMENT, format!("Command data too long: {} bytes (max 65535)", data.len())));
+    }
+    if le.is_some_and(|le| le > 0x10000) {
+        return Err(error(INVALID_ARGUMENT, "Le too large (max 65536)"));
+    }
+    
+    let extended = data.len() > 0xFF || le.is_some_and(|le| le > 0x100);
+    let mut apdu = vec![cla, ins, p1, p2];
+    if !data.is_empty() {
+        if extended {
+            apdu.extend_from_slice(&[0x00, (data.len() >> 8) as u8, data.len() as u8]);
+        } else {
+            apdu.push(data.len() as u8);
+        }
+        apdu.extend_from_slice(data);
+    }
+    if let Some(le) = le {
+        match (extended, data.is_empty()) {
+            (false, _) => apdu.push(le as u8),
+            // Case 2E carries the leading 00; in case 4E it came with Lc
+            (true, true) => apdu.extend_from_slice(&[0x00, (le >> 8) as u8, le as u8]),
+            (true, false) => apdu.extend_from_slice(&[(le >> 8) as u8, le as u8]),
+        }
+    }
+    Ok(Buffer::from(apdu))
+}
+
+/// Compare two ATRs, optionally ignoring the historical bytes (and the TCK that covers them)
+#[napi(catch_unwind)]
+pub fn atr_equal(a: Buffer, b: Buffer, ignore_historical: bool) -> bool {
+    if !ignore_historical {
+        return a.as_ref() == b.as_ref();
+    }
+
+    match (historical_bytes_offset(&a), historical_bytes_offset(&b)) {
+        (Some(a_start), Some(b_start)) => a[..a_start] == b[..b_start],
+        _ => a.as_ref() == b.as_ref(),
+    }
+}
+
+/// Thai ID chip generations by ATR prefix (TS and T0; first match wins)
+///
+/// The historical bytes differ between chip batches, so only the prefix is matched: a
+/// newer batch must still get the GET RESPONSE its generation expects.
+const THAI_ID_GENERATIONS: &[(&[u8], u8)] = &[
+    // Newer chips (e.g. 3B 67 00 00 2D 20 36 00 78 90 00), which answer GET RESPONSE with P2=01
+    (&[0x3B, 0x67], 2),
+    // Original chips (e.g. 3B 68 00 00 00 73 C8 40 12 00 90 00)
+    (&[0x3B, 0x68], 1),
+];
+
+/// Detect the Thai ID chip generation from the ATR
+#[napi(catch_unwind)]
+pub fn thai_id_generation(atr: Buffer) -> Option<u8> {
+    THAI_ID_GENERATIONS.iter()
+        .find(|(known, _)| atr_matches(&atr, known))
+        .map(|(_, generation)| *generation)
+}
+
+/// P2 of the GET RESPONSE a Thai ID chip of `generation` expects after 61xx
+pub(crate) fn thai_id_get_response_p2(generation: Option<u8>) -> u8 {
+    match generation {
+        Some(2) => 0x01,
+        _ => 0x00,
+    }
+}
+
+/// Whether the ATR starts with any of the `known` ATRs or ATR prefixes
+#[napi(catch_unwind)]
+pub fn match_atr(atr: Buffer, known: Vec<Buffer>) -> bool {
+    known.iter().any(|prefix| atr_matches(&atr, prefix))
+}
+
+/// Whether `atr` starts with the non-empty ATR or ATR prefix `known`
+fn atr_matches(atr: &[u8], known: &[u8]) -> bool {
+    !known.is_empty() && atr.starts_with(known)
+}
+
+/// Split an ATR into its convention, T0, indicated protocols and historical bytes
+#[napi(catch_unwind)]
+pub fn parse_atr(atr: Buffer) -> Result<AtrInfo> {
+    let convention = match atr.first() {
+        Some(0x3B) => "direct",
+        Some(0x3F) => "inverse",
+        _ => return Err(error(INVALID_ARGUMENT, format!("Not a valid ATR: {}", hex(&atr)))),
+    };
+    let start = historical_bytes_offset(&atr)
+        .ok_or_else(|| error(INVALID_ARGUMENT, format!("Truncated ATR: {}", hex(&atr))))?;
+    let historical_len = (atr[1] & 0x0F) as usize;
+    
+    Ok(AtrInfo {
+        convention: convention.to_string(),
+        t0: atr[1],
+        protocols: atr_protocols(&atr),
+        historical_bytes: Buffer::from(atr[start..start + historical_len].to_vec()),
+        thai_id_generation: thai_id_generation(atr.clone()),
+    })
+}
+
+/// Describe an ISO 7816-4 status word
+#[napi(catch_unwind)]
+pub fn describe_status(sw1: u8, sw2: u8) -> String {
+    let description = match (sw1, sw2) {
+        (0x90, 0x00) => "Success",
+        (0x61, _) => return format!("Success, {} bytes available with GET RESPONSE", sw2),
+        (0x62, 0x81) => "Part of returned data may be corrupted",
+        (0x62, 0x82) => "End of file reached before reading Le bytes",
+        (0x62, 0x83) => "Selected file deactivated",
+        (0x62, _) => "Warning, state of non-volatile memory unchanged",
+        (0x63, 0xC0..=0xCF) => return format!("Verification failed, {} tries remaining", sw2 & 0x0F),
+        (0x63, _) => "Warning, state of non-volatile memory changed",
+        (0x64, _) => "Execution error, state of non-volatile memory unchanged",
+        (0x65, 0x81) => "Memory failure",
+        (0x65, _) => "Execution error, state of non-volatile memory changed",
+        (0x67, 0x00) => "Wrong length",
+        (0x68, 0x81) => "Logical channel not supported",
+        (0x68, 0x82) => "Secure messaging not supported",
+        (0x68, _) => "Function in CLA not supported",
+        (0x69, 0x81) => "Command incompatible with file structure",
+        (0x69, 0x82) => "Security condition not satisfied",
+        (0x69, 0x83) => "Authentication method blocked",
+        (0x69, 0x84) => "Reference data not usable",
+        (0x69, 0x85) => "Conditions of use not satisfied",
+        (0x69, 0x86) => "Command not allowed (no current EF)",
+        (0x69, 0x87) => "Expected secure messaging data objects missing",
+        (0x69, 0x88) => "Incorrect secure messaging data objects",
+        (0x69, _) => "Command not allowed",
+        (0x6A, 0x80) => "Incorrect parameters in the command data field",
+        (0x6A, 0x81) => "Function not supported",
+        (0x6A, 0x82) => "File or application not found",
+        (0x6A, 0x83) => "Record not found",
+        (0x6A, 0x84) => "Not enough memory space in the file",
+        (0x6A, 0x86) => "Incorrect parameters P1-P2",
+        (0x6A, 0x88) => "Referenced data not found",
+        (0x6A, _) => "Wrong parameters P1-P2",
+        (0x6B, 0x00) => "Wrong parameters (offset outside the EF)",
+        (0x6C, _) => return format!("Wrong Le, exact length is {}", sw2),
+        (0x6D, 0x00) => "Instruction not supported",
+        (0x6E, 0x00) => "Class not supported",
+        (0x6F, 0x00) => "No precise diagnosis",
+        _ => return format!("Unknown status {:02X}{:02X}", sw1, sw2),
+    };
+    description.to_string()
+}
+
+/// Check a 13-digit Thai citizen ID against its mod-11 check digit
+#[napi(catch_unwind)]
+pub fn validate_thai_cid(cid: String) -> bool {
+    let digits: Vec<u32> = cid.chars().filter_map(|c| c.to_digit(10)).collect();
+    if cid.len() != 13 || digits.len() != 13 {
+        return false;
+    }
+    
+    let sum: u32 = digits[..12].iter()
+        .enumerate()
+        .map(|(i, d)| d * (13 - i as u32))
+        .sum();
+    (11 - sum % 11) % 10 == digits[12]
+}
+
+/// Convert a Buddhist-era `YYYYMMDD` date (as stored on Thai ID cards) to an ISO `YYYY-MM-DD` date
+///
+/// Returns null for malformed input and for dates the card marks as unknown with a 00 month or day.
+#[napi(catch_unwind)]
+pub fn thai_date_to_iso(be_date: String) -> Option<String> {
+    let be_date = be_date.trim();
+    if be_date.len() != 8 || !be_date.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    
+    let year: u32 = be_date[..4].parse().ok()?;
+    let month: u32 = be_date[4..6].parse().ok()?;
+    let day: u32 = be_date[6..].parse().ok()?;
+    if year < 543 || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(format!("{:04}-{:02}-{:02}", year - 543, month, day))
+}
+
+/// Hex string of the bytes, uppercase unless `uppercase` is false, optionally with a space between bytes
+#[napi(catch_unwind)]
+pub fn to_hex(data: Buffer, spaced: Option<bool>, uppercase: Option<bool>) -> String {
+    let separator = if spaced.unwrap_or(false) { " " } else { "" };
+    let encoded = data.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(separator);
+    if uppercase.unwrap_or(true) {
+        encoded
+    } else {
+        encoded.to_lowercase()
+    }
+}
+
+/// Bytes of a hex string, in either case; whitespace between digits is ignored
+#[napi(catch_unwind)]
+pub fn from_hex(s: String) -> Result<Buffer> {
+    if let Some((position, c)) = s.char_indices().find(|(_, c)| !c.is_ascii_hexdigit() && !c.is_whitespace()) {
+        return Err(error(INVALID_ARGUMENT, format!("Invalid hex digit {:?} at position {}", c, position)));
+    }
+    parse_hex(&s)
+        .map(Buffer::from)
+        .ok_or_else(|| error(INVALID_ARGUMENT, "Hex string has an odd number of digits"))
+}
+
+/// Decode TIS-620 text (as stored on Thai ID cards), trimming the trailing space padding
+///
+/// The `#` separators between name parts are kept; use `decode_tis620_fields` to split on them.
+#[napi(catch_unwind)]
+pub fn decode_tis620(bytes: Buffer) -> String {
+    tis620_to_string(&bytes).trim_end_matches([' ', '\0']).to_string()
+}
+
+/// Decode TIS-620 text and split it on `#`, trimming each part
+#[napi(catch_unwind)]
+pub fn decode_tis620_fields(bytes: Buffer) -> Vec<String> {
+    tis620_to_string(&bytes)
+        .trim_end_matches([' ', '\0'])
+        .split('#')
+        .map(|field| field.trim().to_string())
+        .collect()
+}
+
+/// TIS-620 to UTF-8: ASCII passes through, 0xA1-0xFB map to U+0E01-U+0E5B
+pub(crate) fn tis620_to_string(bytes: &[u8]) -> String {
+    bytes.iter()
+        .map(|&b| match b {
+            0x00..=0x7F => b as char,
+            0xA1..=0xFB => char::from_u32(0x0E00 + (b as u32 - 0xA0)).unwrap_or(char::REPLACEMENT_CHARACTER),
+            _ => char::REPLACEMENT_CHARACTER,
+        })
+        .collect()
+}
+
+/// TA1 interface byte (FI/DI), if present
+pub(crate) fn ta1(atr: &[u8]) -> Option<u8> {
+    if atr.len() >= 3 && atr[1] & 0x10 != 0 {
+        Some(atr[2])
+    } else {
+        None
+    }
+}
+
+/// File size from an FCP template (tag 80, or 81 when 80 is absent)
+pub(crate) fn fcp_file_size(fcp: &[u8]) -> Option<u32> {
+    let (tag, template) = read_tlv(fcp)?;
+    if tag != 0x62 {
+        return None;
+    }
+    
+    let mut total_size = None;
+    let mut rest = template;
+    while let Some((tag, value)) = read_tlv(rest) {
+        let size = (value.len() <= 4).then(|| value.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32));
+        match tag {
+            0x80 => return size,
+            0x81 => total_size = size,
+            _ => {}
+        }
+        rest = &rest[tlv_len(rest)?..];
+    }
+    total_size
+}
+
+/// Parse one single-byte-tag BER-TLV, returning the tag and value
+pub(crate) fn read_tlv(data: &[u8]) -> Option<(u8, &[u8])> {
+    let total = tlv_len(data)?;
+    let value_len = ber_length(&data[1..])?;
+    Some((data[0], &data[total - value_len.0..total]))
+}
+
+/// Total encoded length of the TLV at the start of `data`
+fn tlv_len(data: &[u8]) -> Option<usize> {
+    let (len, len_bytes) = ber_length(data.get(1..)?)?;
+    let total = 1 + len_bytes + len;
+    (total <= data.len()).then_some(total)
+}
+
+/// BER length (value length, bytes used to encode it)
+fn ber_length(data: &[u8]) -> Option<(usize, usize)> {
+    match *data.first()? {
+        len @ 0x00..=0x7F => Some((len as usize, 1)),
+        0x81 => Some((*data.get(1)? as usize, 2)),
+        0x82 => Some((((*data.get(1)? as usize) << 8) | *data.get(2)? as usize, 3)),
+        _ => None,
+    }
+}
+
+/// Protocols indicated by the TDi bytes (T=0 when there is no TD1)
+///
+/// T=15 only introduces global interface bytes, so it isn't listed.
+fn atr_protocols(atr: &[u8]) -> Vec<u32> {
+    let mut protocols = Vec::new();
+    let mut pos = 1;
+    while let Some(&indicator) = atr.get(pos) {
+        if indicator & 0x80 == 0 {
+            break;
+        }
+        pos += (indicator >> 4).count_ones() as usize;
+        let Some(&td) = atr.get(pos) else { break };
+        let protocol = (td & 0x0F) as u32;
+        if protocol != 15 && !protocols.contains(&protocol) {
+            protocols.push(protocol);
+        }
+    }
+    if protocols.is_empty() {
+        protocols.push(0);
+    }
+    protocols
+}
+
+/// Offset of the first historical byte, found by walking the T0/TDi interface byte chain
+pub(crate) fn historical_bytes_offset(atr: &[u8]) -> Option<usize> {
+    if atr.len() < 2 {
+        return None;
+    }
+
+    let historical_len = (atr[1] & 0x0F) as usize;
+    let mut pos = 1;
+    loop {
+        let indicators = atr[pos] >> 4;
+        let next = pos + indicators.count_ones() as usize;
+        if indicators & 0x08 == 0 {
+            let start = next + 1;
+            return if start + historical_len <= atr.len() { Some(start) } else { None };
+        }
+        if next >= atr.len() {
+            return None;
+        }
+        pos = next;
+    }
+}
+
+/// Standard base64 (with padding) of a byte slice
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |acc, (i, b)| acc | (*b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Uppercase hex string of a byte slice
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Bytes of a hex string (either case, whitespace ignored); None on odd length or a non-hex digit
+pub(crate) fn parse_hex(s: &str) -> Option<Vec<u8>> {
+    let digits = s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<Vec<u8>>>()?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_thai_cid_accepts_valid_check_digits() {
+        assert!(validate_thai_cid("1101700203751".to_string()));
+        assert!(validate_thai_cid("3101200456789".to_string()));
+    }
+
+    #[test]
+    fn validate_thai_cid_rejects_wrong_check_digit() {
+        assert!(!validate_thai_cid("1101700203752".to_string()));
+        assert!(!validate_thai_cid("3101200456780".to_string()));
+    }
+
+    #[test]
+    fn validate_thai_cid_rejects_non_digits() {
+        assert!(!validate_thai_cid("110170020375X".to_string()));
+        assert!(!validate_thai_cid("1-1017-00203-75-1".to_string()));
+        assert!(!validate_thai_cid("๑๑๐๑๗๐๐๒๐๓๗๕๑".to_string()));
+    }
+
+    #[test]
+    fn validate_thai_cid_rejects_wrong_lengths() {
+        assert!(!validate_thai_cid(String::new()));
+        assert!(!validate_thai_cid("110170020375".to_string()));
+        assert!(!validate_thai_cid("11017002037510".to_string()));
+    }
+
+    #[test]
+    fn thai_date_to_iso_converts_buddhist_era() {
+        assert_eq!(thai_date_to_iso("25330415".to_string()), Some("1990-04-15".to_string()));
+        assert_eq!(thai_date_to_iso(" 25671231 ".to_string()), Some("2024-12-31".to_string()));
+    }
+
+    #[test]
+    fn thai_date_to_iso_rejects_unknown_day_or_month() {
+        assert_eq!(thai_date_to_iso("25330400".to_string()), None);
+        assert_eq!(thai_date_to_iso("25330000".to_string()), None);
+    }
+
+    #[test]
+    fn thai_date_to_iso_rejects_invalid_length() {
+        assert_eq!(thai_date_to_iso(String::new()), None);
+        assert_eq!(thai_date_to_iso("2533041".to_string()), None);
+        assert_eq!(thai_date_to_iso("253304150".to_string()), None);
+    }
+
+    #[test]
+    fn atr_protocols_skips_t15() {
+        // TD1 = T=1, TD2 = T=15 with TA3
+        assert_eq!(atr_protocols(&[0x3B, 0x80, 0x81, 0x1F, 0x07]), vec![1]);
+        assert_eq!(atr_protocols(&[0x3B, 0x80, 0x80, 0x81, 0x1F, 0x07]), vec![0, 1]);
+        assert_eq!(atr_protocols(&[0x3B, 0x68, 0x00, 0x00]), vec![0]);
+    }
+
+    #[test]
+    fn thai_id_generation_matches_unlisted_newer_chips() {
+        let atr = Buffer::from(vec![0x3B, 0x67, 0x00, 0x00, 0xA6, 0x40, 0x40, 0x00, 0x09, 0x90, 0x00]);
+        let generation = thai_id_generation(atr);
+        assert_eq!(generation, Some(2));
+        assert_eq!(thai_id_get_response_p2(generation), 0x01);
+    }
+
+    #[test]
+    fn thai_id_generation_ignores_later_atr_bytes() {
+        let listed = Buffer::from(vec![0x3B, 0x68, 0x00, 0x00, 0x00, 0x73, 0xC8, 0x40, 0x12, 0x00, 0x90, 0x00]);
+        let other_batch = Buffer::from(vec![0x3B, 0x68, 0x00, 0x00, 0x00, 0x73, 0xC8, 0x40, 0x13, 0x00, 0x90, 0x00]);
+        assert_eq!(thai_id_generation(listed), Some(1));
+        assert_eq!(thai_id_generation(other_batch), Some(1));
+        assert_eq!(thai_id_generation(Buffer::from(vec![0x3B, 0x69, 0x00, 0x00])), None);
+    }
+}
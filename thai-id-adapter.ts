@@ -214,7 +214,7 @@ export class ThaiIDCardReader {
         const result = card.transmitWithRetry(command, responseLength, 1, 100);
         
         // Success or more data available
-        if (result.sw1 === 0x90 && result.sw2 === 0x00 || result.sw1 === 0x61) {
+        if (result.isSuccess || result.sw1 === 0x61) {
           return result.data;
         }
 